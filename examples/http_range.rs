@@ -0,0 +1,200 @@
+//! Reads an archive over HTTP using range requests, without downloading it first.
+//!
+//! A minimal stand-in server is started locally, so the example is self-contained:
+//!
+//! ```sh
+//! cargo run --example http_range
+//! ```
+
+use sfa::{RandomAccessSource, Reader, Writer};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Archive stored on an HTTP server that supports range requests
+struct HttpSource {
+    addr: SocketAddr,
+    path: String,
+    requests: AtomicUsize,
+}
+
+impl HttpSource {
+    /// Sends a request, returning the status code, content length and body.
+    fn request(
+        &self,
+        method: &str,
+        range: Option<(u64, u64)>,
+    ) -> std::io::Result<(u16, u64, Vec<u8>)> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        let mut stream = TcpStream::connect(self.addr)?;
+        write!(
+            stream,
+            "{method} {} HTTP/1.1\r\nHost: {}\r\n",
+            self.path, self.addr
+        )?;
+        if let Some((start, end)) = range {
+            write!(stream, "Range: bytes={start}-{end}\r\n")?;
+        }
+        write!(stream, "Connection: close\r\n\r\n")?;
+
+        let mut reader = BufReader::new(stream);
+
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line)?;
+        let status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| std::io::Error::other("invalid status line"))?;
+
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().map_err(std::io::Error::other)?;
+                }
+            }
+        }
+
+        let mut body = vec![];
+        if method != "HEAD" {
+            reader.take(content_length).read_to_end(&mut body)?;
+        }
+
+        Ok((status, content_length, body))
+    }
+}
+
+impl RandomAccessSource for HttpSource {
+    fn size(&self) -> std::io::Result<u64> {
+        let (_, content_length, _) = self.request("HEAD", None)?;
+        Ok(content_length)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        if buf.is_empty() {
+            return Ok(());
+        }
+
+        let end = offset + buf.len() as u64 - 1;
+        let (status, _, body) = self.request("GET", Some((offset, end)))?;
+
+        if status != 206 || body.len() != buf.len() {
+            return Err(std::io::Error::other(format!(
+                "unexpected response: {status} with {} bytes",
+                body.len(),
+            )));
+        }
+
+        buf.copy_from_slice(&body);
+        Ok(())
+    }
+}
+
+/// Serves `bytes` to any request, honoring single byte ranges.
+fn serve(listener: TcpListener, bytes: Vec<u8>) {
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let is_head = request_line.starts_with("HEAD");
+
+        let mut range = None;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Range: bytes=") {
+                let (start, end) = value.split_once('-').unwrap();
+                range = Some((
+                    start.parse::<usize>().unwrap(),
+                    end.parse::<usize>().unwrap(),
+                ));
+            }
+        }
+
+        let (status, body) = match range {
+            Some((start, end)) if start <= end && end < bytes.len() => {
+                ("206 Partial Content", &bytes[start..=end])
+            }
+            Some(_) => ("416 Range Not Satisfiable", &[][..]),
+            None => ("200 OK", &bytes[..]),
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len(),
+        )
+        .unwrap();
+        if !is_head {
+            stream.write_all(body).unwrap();
+        }
+    }
+}
+
+fn main() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let bytes = std::fs::read(&path)?;
+    std::thread::spawn(move || serve(listener, bytes));
+
+    let source = HttpSource {
+        addr,
+        path: "/cherry_pie.sfa".into(),
+        requests: AtomicUsize::default(),
+    };
+
+    let reader = Reader::from_source(&source)?;
+    println!(
+        "Opened archive with {} sections in {} requests",
+        reader.toc().len(),
+        source.requests.load(Ordering::Relaxed),
+    );
+
+    for entry in reader.toc().iter() {
+        let mut content = vec![];
+        entry.reader(&source).read_to_end(&mut content)?;
+
+        println!(
+            "{}: {:?}",
+            String::from_utf8_lossy(entry.name()),
+            String::from_utf8_lossy(&content),
+        );
+    }
+
+    let chorus = reader.toc().section(b"Chorus").expect("should exist");
+    let mut content = vec![];
+    chorus.reader(&source).read_to_end(&mut content)?;
+    assert_eq!(b"Youth is running out, we finally feel it now\n", &*content);
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Merges multiple archives into one.
+//!
+//! ```sh
+//! cargo run --example merge -- [--prefix | --keep-last] <output> <archive>...
+//! ```
+//!
+//! By default, merging fails if a section name occurs in more than one archive.
+
+use sfa::{ConflictPolicy, Merger, Writer};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut conflict_policy = ConflictPolicy::Error;
+    let mut paths = vec![];
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--prefix" => conflict_policy = ConflictPolicy::Prefix,
+            "--keep-last" => conflict_policy = ConflictPolicy::KeepLast,
+            _ => paths.push(arg),
+        }
+    }
+
+    let Some((output, sources)) = paths.split_first() else {
+        return Err("usage: merge [--prefix | --keep-last] <output> <archive>...".into());
+    };
+
+    let mut merger = Merger::new().use_conflict_policy(conflict_policy);
+    for source in sources {
+        merger = merger.add_archive(source)?;
+    }
+
+    let archive = merger.merge_into(Writer::new_atomic(output)?)?;
+
+    println!(
+        "Merged {} archives into {output} ({} sections, {} bytes)",
+        sources.len(),
+        archive.toc().len(),
+        archive.file_size(),
+    );
+
+    Ok(())
+}
@@ -40,8 +40,11 @@ fn main() {
                     content.push(0);
                 }
 
-                writer.start(name.as_bytes()).unwrap();
-                writer.write_all(&content).unwrap();
+                writer
+                    .section(name.as_bytes())
+                    .unwrap()
+                    .write_all(&content)
+                    .unwrap();
 
                 expected.push((name, content));
             }
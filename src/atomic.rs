@@ -0,0 +1,188 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Atomic archive creation
+//!
+//! The archive is written to a temporary file next to its destination, which is
+//! moved into place once the archive is finished and synced, so readers never
+//! observe a partially written archive at the destination path.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// What to do if the destination of an atomic writer already exists
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Fail with [`std::io::ErrorKind::AlreadyExists`]
+    #[default]
+    Fail,
+
+    /// Atomically replace the existing file
+    Replace,
+}
+
+/// Counter to make temporary file names unique within the process
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+pub struct AtomicTarget {
+    pub tmp_path: PathBuf,
+    pub path: PathBuf,
+    pub overwrite_policy: OverwritePolicy,
+}
+
+impl AtomicTarget {
+    /// Creates a temporary file next to `path`.
+    pub fn create(path: PathBuf) -> std::io::Result<(Self, File)> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "archive path has no file name",
+                )
+            })?
+            .to_string_lossy();
+
+        let tmp_path = path.with_file_name(format!(
+            ".{file_name}.{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+
+        log::trace!("Creating temporary file {}", tmp_path.display());
+
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)?;
+
+        Ok((
+            Self {
+                tmp_path,
+                path,
+                overwrite_policy: OverwritePolicy::default(),
+            },
+            file,
+        ))
+    }
+
+    /// Moves the (synced) temporary file to its destination and syncs the directory.
+    pub fn commit(&self) -> std::io::Result<()> {
+        self.persist()?;
+
+        if let Some(dir) = self.path.parent() {
+            sync_dir(dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the temporary file to its destination, without syncing the directory.
+    pub fn persist(&self) -> std::io::Result<()> {
+        log::trace!(
+            "Moving {} to {}",
+            self.tmp_path.display(),
+            self.path.display(),
+        );
+
+        match self.overwrite_policy {
+            OverwritePolicy::Replace => std::fs::rename(&self.tmp_path, &self.path)?,
+            OverwritePolicy::Fail => {
+                // NOTE: Unlike renaming, linking fails if the destination exists
+                match std::fs::hard_link(&self.tmp_path, &self.path) {
+                    Ok(()) => std::fs::remove_file(&self.tmp_path)?,
+                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                        self.remove_tmp();
+                        return Err(e);
+                    }
+                    Err(e) => {
+                        // NOTE: The file system may not support hard links, so fall back to
+                        // renaming, which is only safe if the destination does not exist
+                        log::debug!(
+                            "Failed to link temporary file, falling back to renaming: {e:?}"
+                        );
+
+                        if self.path.try_exists()? {
+                            self.remove_tmp();
+                            return Err(std::io::ErrorKind::AlreadyExists.into());
+                        }
+
+                        // NOTE: If renaming fails as well, the temporary file is kept, so the archive is not lost
+                        if let Err(e) = std::fs::rename(&self.tmp_path, &self.path) {
+                            log::error!(
+                                "Failed to move temporary file, keeping it at {}: {e:?}",
+                                self.tmp_path.display(),
+                            );
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the temporary file, logging a warning if that fails.
+    pub fn remove_tmp(&self) {
+        if let Err(e) = std::fs::remove_file(&self.tmp_path) {
+            log::warn!("Failed to remove temporary file: {e:?}");
+        }
+    }
+}
+
+/// Syncs the directory, so renames and new files in it are durable.
+#[cfg(unix)]
+pub fn sync_dir(path: &Path) -> std::io::Result<()> {
+    File::open(path)?.sync_all()
+}
+
+/// Syncs the directory, so renames and new files in it are durable.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+pub fn sync_dir(_path: &Path) -> std::io::Result<()> {
+    // NOTE: Directories cannot be opened (and synced) like files on Windows
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn persist_keeps_tmp_file_on_failure() {
+        // NOTE: Moving a file from one file system to another fails
+        let shm = Path::new("/dev/shm");
+        if !shm.is_dir() {
+            return;
+        }
+
+        let tmp_dir = tempfile::tempdir_in(shm).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let tmp_path = tmp_dir.path().join("tmp");
+        std::fs::write(&tmp_path, b"Glazed eyes and cherry pie\n").unwrap();
+
+        let target = AtomicTarget {
+            tmp_path,
+            path: dir.path().join("cherry_pie"),
+            overwrite_policy: OverwritePolicy::Fail,
+        };
+
+        assert!(target.persist().is_err());
+        assert!(!target.path.try_exists().unwrap());
+        assert_eq!(
+            b"Glazed eyes and cherry pie\n",
+            &*std::fs::read(&target.tmp_path).unwrap(),
+        );
+    }
+}
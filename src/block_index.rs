@@ -0,0 +1,327 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Block checksums
+//!
+//! If enabled, the checksums of fixed-size blocks of each section are stored in the block index,
+//! which is located directly before the mirrored table of contents (or the table of contents),
+//! so a section can be verified block by block while it is read.
+//!
+//! Sections are identified by their position and length, so aliases sharing all bytes of
+//! a section (including deduplicated sections) share its block checksums as well.
+//!
+//! The digest of the whole block index is stored in the trailer, so the block checksums are
+//! covered by the trailer checksum and the signature (if any).
+
+use crate::{
+    checksum::{ChecksumType, Hasher},
+    trailer::reader::{ParsedTrailer, TrailerReader},
+    Checksum, ReaderOptions,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+    sync::Arc,
+};
+
+pub const BLOCK_INDEX_MAGIC: &[u8] = b"BLK!";
+
+/// Size of the footer that ends the block index
+pub const FOOTER_SIZE: usize = 4 + 8 + 8 + BLOCK_INDEX_MAGIC.len();
+
+/// Checksums of the blocks of a section
+#[derive(Clone, Debug)]
+pub struct BlockChecksums {
+    pub block_size: u32,
+    checksum_type: ChecksumType,
+
+    /// Digests of all blocks, stored back to back
+    digests: Vec<u8>,
+}
+
+impl BlockChecksums {
+    /// Returns the checksum of block `idx`.
+    pub fn get(&self, idx: usize) -> Option<Checksum> {
+        let digest_len = self.checksum_type.digest_len();
+        let digest = self
+            .digests
+            .get(idx.checked_mul(digest_len)?..)?
+            .get(..digest_len)?;
+
+        Some(Checksum::from_bytes(self.checksum_type, digest))
+    }
+
+    /// Returns the checksums of all blocks.
+    pub fn iter(&self) -> impl Iterator<Item = Checksum> + '_ {
+        self.digests
+            .chunks_exact(self.checksum_type.digest_len())
+            .map(|digest| Checksum::from_bytes(self.checksum_type, digest))
+    }
+}
+
+/// Block checksums of all sections, by position and length of the section
+pub type BlockIndex = HashMap<(u64, u64), Arc<BlockChecksums>>;
+
+/// Returns the number of blocks of a section.
+pub fn block_count(len: u64, block_size: u32) -> u64 {
+    len.div_ceil(u64::from(block_size))
+}
+
+/// Calculates the block checksums of the section being written
+pub struct BlockHasher {
+    checksum_type: ChecksumType,
+    block_size: u32,
+    hasher: Hasher,
+
+    /// Number of bytes hashed of the current block
+    filled: u64,
+
+    digests: Vec<u8>,
+}
+
+impl BlockHasher {
+    pub fn new(checksum_type: ChecksumType, block_size: u32) -> Self {
+        Self {
+            checksum_type,
+            block_size,
+            hasher: Hasher::new(checksum_type),
+            filled: 0,
+            digests: Vec::new(),
+        }
+    }
+
+    pub fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    pub fn update(&mut self, mut buf: &[u8]) {
+        while !buf.is_empty() {
+            let n = usize::try_from(u64::from(self.block_size) - self.filled)
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            self.hasher.update(&buf[..n]);
+
+            #[allow(clippy::indexing_slicing)]
+            {
+                buf = &buf[n..];
+            }
+
+            self.filled += n as u64;
+
+            if self.filled == u64::from(self.block_size) {
+                self.finish_block();
+            }
+        }
+    }
+
+    fn finish_block(&mut self) {
+        let hasher = std::mem::replace(&mut self.hasher, Hasher::new(self.checksum_type));
+        self.digests.extend_from_slice(hasher.checksum().as_bytes());
+        self.filled = 0;
+    }
+
+    /// Returns the block checksums of the section, and resets the hasher for the next section.
+    pub fn finish(&mut self) -> BlockChecksums {
+        if self.filled > 0 {
+            self.finish_block();
+        }
+
+        BlockChecksums {
+            block_size: self.block_size,
+            checksum_type: self.checksum_type,
+            digests: std::mem::take(&mut self.digests),
+        }
+    }
+}
+
+/// Writes the block index, returning its digest, which needs to be stored in the trailer.
+pub fn write_into(
+    mut writer: impl Write,
+    sections: &[((u64, u64), BlockChecksums)],
+    checksum_type: ChecksumType,
+) -> std::io::Result<Checksum> {
+    use byteorder::LE;
+
+    log::trace!("Writing block index");
+
+    let mut buf = vec![];
+
+    for ((pos, len), checksums) in sections {
+        buf.write_u64::<LE>(*pos)?;
+        buf.write_u64::<LE>(*len)?;
+        buf.write_u32::<LE>(checksums.block_size)?;
+
+        buf.write_all(&checksums.digests)?;
+    }
+
+    let checksum = xxhash_rust::xxh3::xxh3_64(&buf);
+    let len = buf.len() as u64;
+
+    #[allow(clippy::expect_used)]
+    buf.write_u32::<LE>(
+        u32::try_from(sections.len())
+            .expect("block index should not have 4 billion or more entries"),
+    )?;
+    buf.write_u64::<LE>(len)?;
+    buf.write_u64::<LE>(checksum)?;
+    buf.write_all(BLOCK_INDEX_MAGIC)?;
+
+    writer.write_all(&buf)?;
+
+    let mut hasher = Hasher::new(checksum_type);
+    hasher.update(&buf);
+
+    Ok(hasher.checksum())
+}
+
+/// Returns the position where the block index is expected to end,
+/// which is the start of the mirrored table of contents (or the table of contents).
+pub fn end<R: Read + Seek>(reader: &mut R, trailer: &ParsedTrailer) -> crate::Result<Option<u64>> {
+    if !trailer.has_toc_mirror() {
+        return Ok(Some(trailer.toc_pos));
+    }
+
+    let mirror_end = TrailerReader::mirror_end(reader, trailer)?;
+
+    Ok(mirror_end
+        .checked_sub(trailer.size())
+        .and_then(|pos| pos.checked_sub(trailer.toc_len)))
+}
+
+/// Reads the block index of the archive, and checks it against the digest in the trailer.
+///
+/// Returns the position of the block index, and the block checksums of all sections.
+pub fn read<R: Read + Seek>(
+    reader: &mut R,
+    trailer: &ParsedTrailer,
+    options: &ReaderOptions,
+) -> crate::Result<(u64, BlockIndex)> {
+    use byteorder::LE;
+
+    log::trace!("Reading block index");
+
+    let invalid = |offset| crate::Error::InvalidBlockIndex { path: None, offset };
+
+    let Some(end) = self::end(reader, trailer)? else {
+        log::error!("Invalid block index position");
+        return Err(invalid(trailer.toc_pos));
+    };
+
+    let Some(expected) = trailer.block_index_digest else {
+        log::error!("Trailer does not contain a block index digest");
+        return Err(invalid(trailer.pos));
+    };
+
+    let checksum_type = expected.checksum_type();
+
+    let Some(footer_pos) = end.checked_sub(FOOTER_SIZE as u64) else {
+        log::error!("Invalid block index position");
+        return Err(invalid(end));
+    };
+
+    reader.seek(SeekFrom::Start(footer_pos))?;
+
+    let count = reader.read_u32::<LE>()?;
+    let len = reader.read_u64::<LE>()?;
+    let checksum = reader.read_u64::<LE>()?;
+
+    {
+        let mut buf = [0u8; BLOCK_INDEX_MAGIC.len()];
+        reader.read_exact(&mut buf)?;
+
+        if buf != BLOCK_INDEX_MAGIC {
+            log::error!("Invalid block index footer");
+            return Err(invalid(footer_pos));
+        }
+    }
+
+    if len > options.max_block_index_size {
+        log::error!(
+            "Block index length {len} exceeds limit of {}",
+            options.max_block_index_size,
+        );
+        return Err(crate::Error::BlockIndexTooLarge {
+            path: None,
+            offset: footer_pos,
+            len,
+            limit: options.max_block_index_size,
+        });
+    }
+
+    let Some(pos) = footer_pos.checked_sub(len) else {
+        log::error!("Block index length {len} exceeds file");
+        return Err(invalid(footer_pos));
+    };
+
+    reader.seek(SeekFrom::Start(pos))?;
+
+    // NOTE: The block index is located before the footer, so its length is bounded by the file size
+    #[allow(clippy::cast_possible_truncation)]
+    let mut bytes = vec![0; (end - pos) as usize];
+    reader.read_exact(&mut bytes)?;
+
+    {
+        let mut hasher = Hasher::new(checksum_type);
+        hasher.update(&bytes);
+
+        if hasher.checksum() != expected {
+            log::error!("Block index digest mismatch");
+            return Err(invalid(pos));
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::indexing_slicing)]
+    let bytes = &bytes[..len as usize];
+
+    if xxhash_rust::xxh3::xxh3_64(bytes) != checksum {
+        log::error!("Block index checksum mismatch");
+        return Err(invalid(pos));
+    }
+
+    let mut reader = bytes;
+    let mut index = BlockIndex::new();
+    let digest_len = checksum_type.digest_len() as u64;
+
+    for _ in 0..count {
+        let section_pos = reader.read_u64::<LE>()?;
+        let section_len = reader.read_u64::<LE>()?;
+        let block_size = reader.read_u32::<LE>()?;
+
+        if block_size == 0 {
+            log::error!("Invalid block size 0");
+            return Err(invalid(pos));
+        }
+
+        // NOTE: The block count is derived from the untrusted section length,
+        // so check the digests fit into the remaining bytes before allocating them
+        let digest_bytes = block_count(section_len, block_size).saturating_mul(digest_len);
+        if digest_bytes > reader.len() as u64 {
+            log::error!("Block index is truncated");
+            return Err(invalid(pos));
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut digests = vec![0; digest_bytes as usize];
+        reader.read_exact(&mut digests)?;
+
+        index.insert(
+            (section_pos, section_len),
+            Arc::new(BlockChecksums {
+                block_size,
+                checksum_type,
+                digests,
+            }),
+        );
+    }
+
+    if !reader.is_empty() {
+        log::error!("Block index has {} trailing bytes", reader.len());
+        return Err(invalid(pos));
+    }
+
+    Ok((pos, index))
+}
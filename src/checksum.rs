@@ -2,29 +2,266 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-/// An 128-bit checksum
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Checksum(u128);
+/// Maximum digest length of all supported checksum algorithms
+pub const MAX_DIGEST_LEN: usize = 32;
+
+/// Checksum algorithm
+///
+/// The checksum type is stored in the trailer and applies to all checksums
+/// in the archive (table of contents checksum and full-file checksum).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ChecksumType {
+    /// 128-bit XXH3
+    #[default]
+    Xxh3,
+
+    /// 32-bit CRC32C (Castagnoli)
+    #[cfg(feature = "crc32c")]
+    Crc32c,
+
+    /// 256-bit BLAKE3
+    #[cfg(feature = "blake3")]
+    Blake3,
+
+    /// 256-bit SHA-256
+    #[cfg(feature = "sha256")]
+    Sha256,
+}
+
+impl ChecksumType {
+    /// Returns the digest length in bytes.
+    #[must_use]
+    pub fn digest_len(self) -> usize {
+        match self {
+            Self::Xxh3 => 16,
+
+            #[cfg(feature = "crc32c")]
+            Self::Crc32c => 4,
+
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => 32,
+
+            #[cfg(feature = "sha256")]
+            Self::Sha256 => 32,
+        }
+    }
+
+    /// Returns `true` if the checksum type is a cryptographic hash function.
+    ///
+    /// Only cryptographic checksums protect the section contents of a signed archive,
+    /// because colliding section bytes can be crafted for the other checksum types.
+    #[must_use]
+    pub fn is_cryptographic(self) -> bool {
+        match self {
+            Self::Xxh3 => false,
+
+            #[cfg(feature = "crc32c")]
+            Self::Crc32c => false,
+
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => true,
+
+            #[cfg(feature = "sha256")]
+            Self::Sha256 => true,
+        }
+    }
+}
+
+impl From<ChecksumType> for u8 {
+    fn from(value: ChecksumType) -> Self {
+        match value {
+            ChecksumType::Xxh3 => 0x0,
+
+            #[cfg(feature = "crc32c")]
+            ChecksumType::Crc32c => 0x1,
+
+            #[cfg(feature = "blake3")]
+            ChecksumType::Blake3 => 0x2,
+
+            #[cfg(feature = "sha256")]
+            ChecksumType::Sha256 => 0x3,
+        }
+    }
+}
+
+impl ChecksumType {
+    /// Parses a checksum type from its on-disk ID.
+    ///
+    /// Returns `None` if the checksum type is unknown, or its feature is not enabled.
+    pub(crate) fn from_id(value: u8) -> Option<Self> {
+        match value {
+            0x0 => Some(Self::Xxh3),
+
+            #[cfg(feature = "crc32c")]
+            0x1 => Some(Self::Crc32c),
+
+            #[cfg(feature = "blake3")]
+            0x2 => Some(Self::Blake3),
+
+            #[cfg(feature = "sha256")]
+            0x3 => Some(Self::Sha256),
+
+            _ => None,
+        }
+    }
+}
+
+/// Incremental hasher for a [`ChecksumType`]
+#[derive(Clone)]
+pub enum Hasher {
+    Xxh3(Box<xxhash_rust::xxh3::Xxh3Default>),
+
+    #[cfg(feature = "crc32c")]
+    Crc32c(u32),
+
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+
+    #[cfg(feature = "sha256")]
+    Sha256(Box<sha2::Sha256>),
+}
+
+impl Hasher {
+    pub fn new(checksum_type: ChecksumType) -> Self {
+        match checksum_type {
+            ChecksumType::Xxh3 => Self::Xxh3(Box::default()),
+
+            #[cfg(feature = "crc32c")]
+            ChecksumType::Crc32c => Self::Crc32c(0),
+
+            #[cfg(feature = "blake3")]
+            ChecksumType::Blake3 => Self::Blake3(Box::default()),
+
+            #[cfg(feature = "sha256")]
+            ChecksumType::Sha256 => Self::Sha256(Box::default()),
+        }
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        match self {
+            Self::Xxh3(hasher) => hasher.update(buf),
+
+            #[cfg(feature = "crc32c")]
+            Self::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, buf),
+
+            #[cfg(feature = "blake3")]
+            Self::Blake3(hasher) => {
+                hasher.update(buf);
+            }
+
+            #[cfg(feature = "sha256")]
+            Self::Sha256(hasher) => sha2::Digest::update(&mut **hasher, buf),
+        }
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        match self {
+            Self::Xxh3(hasher) => Checksum::from_raw(hasher.digest128()),
+
+            #[cfg(feature = "crc32c")]
+            Self::Crc32c(crc) => Checksum::from_bytes(ChecksumType::Crc32c, &crc.to_le_bytes()),
+
+            #[cfg(feature = "blake3")]
+            Self::Blake3(hasher) => {
+                Checksum::from_bytes(ChecksumType::Blake3, hasher.finalize().as_bytes())
+            }
+
+            #[cfg(feature = "sha256")]
+            Self::Sha256(hasher) => Checksum::from_bytes(
+                ChecksumType::Sha256,
+                &sha2::Digest::finalize((**hasher).clone()),
+            ),
+        }
+    }
+}
+
+/// A checksum of any supported [`ChecksumType`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Checksum {
+    checksum_type: ChecksumType,
+    bytes: [u8; MAX_DIGEST_LEN],
+}
 
 impl Checksum {
+    /// Creates a XXH3 checksum from its integer representation.
     pub(crate) fn from_raw(value: u128) -> Self {
-        Self(value)
+        Self::from_bytes(ChecksumType::Xxh3, &value.to_le_bytes())
+    }
+
+    /// Creates a checksum from its digest bytes.
+    ///
+    /// `digest` should be exactly [`ChecksumType::digest_len`] bytes long.
+    pub(crate) fn from_bytes(checksum_type: ChecksumType, digest: &[u8]) -> Self {
+        let mut bytes = [0; MAX_DIGEST_LEN];
+
+        for (dst, src) in bytes.iter_mut().zip(digest) {
+            *dst = *src;
+        }
+
+        Self {
+            checksum_type,
+            bytes,
+        }
+    }
+
+    /// Returns the checksum algorithm.
+    #[must_use]
+    pub fn checksum_type(&self) -> ChecksumType {
+        self.checksum_type
+    }
+
+    /// Returns the digest bytes.
+    ///
+    /// Integer digests (XXH3, CRC32C) are little-endian encoded.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes
+            .get(..self.checksum_type.digest_len())
+            .unwrap_or(&self.bytes)
+    }
+
+    /// Returns the digest bytes, zero-padded to the maximum digest length.
+    pub(crate) fn as_padded_bytes(&self) -> &[u8; MAX_DIGEST_LEN] {
+        &self.bytes
     }
 
     /// Converts the checksum to integer.
+    ///
+    /// Digests wider than 128 bits are truncated to their first 16 bytes.
     #[must_use]
     pub fn into_u128(self) -> u128 {
-        self.0
+        let mut buf = [0; 16];
+        buf.copy_from_slice(self.bytes.get(..16).unwrap_or(&[0; 16]));
+        u128::from_le_bytes(buf)
     }
 
-    pub(crate) fn check(&self, expected: Self) -> crate::Result<()> {
+    /// Checks the checksum against the expected checksum of the section
+    /// (or table of contents, if `section` is `None`) at `offset`.
+    pub(crate) fn check(
+        &self,
+        expected: Self,
+        section: Option<&[u8]>,
+        offset: u64,
+    ) -> crate::Result<()> {
         if self == &expected {
             Ok(())
         } else {
             Err(crate::Error::ChecksumMismatch {
-                expected,
-                got: *self,
+                path: None,
+                section: section.map(<[u8]>::to_vec),
+                offset,
+                expected: Box::new(expected),
+                got: Box::new(*self),
             })
         }
     }
 }
+
+impl std::fmt::Display for Checksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{
+    checksum::{ChecksumType, Hasher},
+    Checksum,
+};
+
+pub struct ChecksummedReader<R: std::io::Read> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: std::io::Read> ChecksummedReader<R> {
+    pub fn new(reader: R, checksum_type: ChecksumType) -> Self {
+        Self {
+            inner: reader,
+            hasher: Hasher::new(checksum_type),
+        }
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        self.hasher.checksum()
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for ChecksummedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.hasher.update(&buf[..n]);
+
+        Ok(n)
+    }
+}
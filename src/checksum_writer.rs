@@ -1,20 +1,56 @@
-use crate::Checksum;
+use crate::{
+    checksum::{ChecksumType, Hasher},
+    Checksum,
+};
 
 pub struct ChecksummedWriter<W: std::io::Write> {
     inner: W,
-    hasher: xxhash_rust::xxh3::Xxh3Default,
+    hasher: Hasher,
+
+    /// State of the hasher at the last checkpoint
+    checkpoint: Option<Hasher>,
 }
 
 impl<W: std::io::Write> ChecksummedWriter<W> {
-    pub fn new(writer: W) -> Self {
+    pub fn new(writer: W, checksum_type: ChecksumType) -> Self {
         Self {
             inner: writer,
-            hasher: xxhash_rust::xxh3::Xxh3Default::new(),
+            hasher: Hasher::new(checksum_type),
+            checkpoint: None,
+        }
+    }
+
+    pub fn set_checksum_type(&mut self, checksum_type: ChecksumType) {
+        self.hasher = Hasher::new(checksum_type);
+        self.checkpoint = None;
+    }
+
+    /// Remembers the current state of the hasher, so data written after it can be rolled back.
+    pub fn set_checkpoint(&mut self) {
+        self.checkpoint = Some(self.hasher.clone());
+    }
+
+    /// Restores the hasher to the last checkpoint, after the data written since
+    /// was removed from the inner writer.
+    ///
+    /// Returns `false` if there is no checkpoint.
+    pub fn rollback(&mut self) -> bool {
+        match self.checkpoint.take() {
+            Some(hasher) => {
+                self.hasher = hasher;
+                true
+            }
+            None => false,
         }
     }
 
     pub fn checksum(&self) -> Checksum {
-        Checksum::from_raw(self.hasher.digest128())
+        self.hasher.checksum()
+    }
+
+    /// Hashes data that was written to the inner writer by other means.
+    pub fn update(&mut self, buf: &[u8]) {
+        self.hasher.update(buf);
     }
 
     pub fn inner(&mut self) -> &mut W {
@@ -28,7 +64,11 @@ impl<W: std::io::Write> std::io::Write for ChecksummedWriter<W> {
     }
 
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.hasher.update(buf);
-        self.inner.write(buf)
+        let n = self.inner.write(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.hasher.update(&buf[..n]);
+
+        Ok(n)
     }
 }
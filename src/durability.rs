@@ -0,0 +1,174 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::atomic::{sync_dir, AtomicTarget};
+use std::{
+    collections::HashSet,
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// How an archive is persisted when the [`Writer`](crate::Writer) is finished
+#[derive(Clone, Debug, Default)]
+pub enum Durability {
+    /// Do not sync the file, leaving it to the OS to write it back eventually
+    ///
+    /// Use this for scratch archives that do not need to survive a crash.
+    None,
+
+    /// Sync the file data (and the metadata needed to read it back), using [`File::sync_data`]
+    SyncData,
+
+    /// Sync the file data and all metadata, using [`File::sync_all`]
+    #[default]
+    SyncAll,
+
+    /// Defer syncing to the given [`SyncGroup`], so many archives can be synced in one batch
+    Group(SyncGroup),
+}
+
+#[derive(Debug)]
+struct PendingSync {
+    file: File,
+    path: Option<PathBuf>,
+    target: Option<AtomicTarget>,
+}
+
+impl PendingSync {
+    /// Syncs the archive and moves it into place (if atomic),
+    /// adding the directory that needs to be synced to `dirs`.
+    fn commit(&self, dirs: &mut HashSet<PathBuf>) -> crate::Result<()> {
+        let path = self
+            .target
+            .as_ref()
+            .map(|target| &target.path)
+            .or(self.path.as_ref());
+
+        let with_path = |e| match path {
+            Some(path) => crate::Error::io_at(e, path),
+            None => crate::Error::Io(e),
+        };
+
+        if let Err(e) = self.file.sync_data() {
+            // NOTE: The archive is not durable, so it is not moved into place
+            if let Some(target) = &self.target {
+                target.remove_tmp();
+            }
+
+            return Err(with_path(e));
+        }
+
+        if let Some(target) = &self.target {
+            target.persist().map_err(with_path)?;
+
+            if let Some(dir) = target.path.parent() {
+                dirs.insert(dir.to_path_buf());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Group of finished archives that are synced together
+///
+/// Writers using [`Durability::Group`] register their file with the group when finished,
+/// instead of syncing it. The archives are only durable after [`SyncGroup::commit`] returns.
+///
+/// Archives of atomic writers (see [`Writer::new_atomic`](crate::Writer::new_atomic))
+/// are moved into place by [`SyncGroup::commit`] after being synced, so they never
+/// become visible before they are durable. Only their directories are synced,
+/// because other writers do not know the path of their file.
+///
+/// ```
+/// use sfa::{Durability, SyncGroup, Writer};
+/// use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+///
+/// let group = SyncGroup::default();
+///
+/// for idx in 0..10 {
+///     let mut writer = Writer::new_at_path(dir.path().join(format!("{idx}.sfa")))?
+///         .use_durability(Durability::Group(group.clone()));
+///     writer.section("Section 1")?.write_all(b"Hello world!\n")?;
+///     writer.finish()?;
+/// }
+///
+/// assert_eq!(10, group.len());
+/// group.commit()?;
+/// assert!(group.is_empty());
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SyncGroup {
+    pending: Arc<Mutex<Vec<PendingSync>>>,
+}
+
+impl SyncGroup {
+    pub(crate) fn defer(&self, file: File, path: Option<PathBuf>, target: Option<AtomicTarget>) {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(PendingSync { file, path, target });
+    }
+
+    /// Returns the number of archives that are waiting to be synced.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no archives are waiting to be synced.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Syncs all pending archives, moves the archives of atomic writers into place,
+    /// and syncs their directories.
+    ///
+    /// All archives are committed, even if some of them fail.
+    /// The group is empty afterwards.
+    ///
+    /// # Errors
+    ///
+    /// If any archive (or directory) fails, returns [`Error::CommitFailed`](crate::Error::CommitFailed)
+    /// containing all failures. The temporary files of atomic writers that failed are removed,
+    /// unless moving them into place failed for another reason than the destination
+    /// already existing (see [`Writer::new_atomic`](crate::Writer::new_atomic)).
+    pub fn commit(&self) -> crate::Result<()> {
+        let pending =
+            std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+
+        log::trace!("Syncing {} archives", pending.len());
+
+        let mut errors = vec![];
+        let mut dirs = HashSet::new();
+
+        for item in &pending {
+            if let Err(e) = item.commit(&mut dirs) {
+                log::error!("Failed to commit archive: {e}");
+                errors.push(e);
+            }
+        }
+
+        for dir in dirs {
+            if let Err(e) = sync_dir(&dir) {
+                log::error!("Failed to sync directory {}: {e:?}", dir.display());
+                errors.push(crate::Error::io_at(e, &dir));
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        Err(crate::Error::CommitFailed { errors })
+    }
+}
@@ -2,36 +2,682 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::checksum::Checksum;
+use crate::{checksum::Checksum, toc::entry::SectionName};
+use std::path::{Path, PathBuf};
 
 /// Error type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// IO error
     Io(std::io::Error),
 
-    /// Invalid header
-    InvalidHeader,
+    /// IO error while accessing the file at `path`
+    FileIo {
+        /// Path of the file
+        path: PathBuf,
+
+        /// The underlying IO error
+        inner: std::io::Error,
+    },
+
+    /// The archive does not end with a trailer
+    ///
+    /// This typically means the archive was not finished, e.g. because the writer
+    /// was dropped or the process crashed while writing it. If a trailer (with corrupted
+    /// or cut off magic bytes) pointing to a table of contents is found, or the mirrored trailer
+    /// can be used, the archive is not reported as incomplete, so it is safe to remove
+    /// incomplete archives.
+    /// Sections may be recoverable using [`Reader::salvage`](crate::Reader::salvage).
+    IncompleteArchive {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Size of the file
+        len: u64,
+    },
+
+    /// The archive was finished, but its end was cut off
+    ///
+    /// A trailer with missing bytes was found, whose table of contents ends right before it.
+    TruncatedArchive {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Size of the file
+        len: u64,
+
+        /// Size of the file implied by the trailer
+        expected_len: u64,
+    },
+
+    /// The trailer magic bytes are missing
+    InvalidTrailerMagic {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the trailer
+        offset: u64,
+    },
 
     /// Unsupported file format version
-    InvalidVersion,
+    UnsupportedVersion {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the trailer
+        offset: u64,
+
+        /// The version stored in the trailer
+        version: u8,
+    },
 
     /// Unsupported checksum type
-    UnsupportedChecksumType,
+    UnsupportedChecksumType {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the trailer (or section marker)
+        offset: u64,
+
+        /// The checksum type stored in the archive
+        checksum_type: u8,
+    },
+
+    /// The trailer is corrupted
+    TrailerChecksumMismatch {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the trailer
+        offset: u64,
+
+        /// The calculated checksum
+        got: u64,
+
+        /// The expected checksum as defined in the trailer
+        expected: u64,
+    },
+
+    /// The table of contents magic bytes are missing
+    InvalidTocMagic {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+    },
+
+    /// The table of contents does not end where the trailer (or signature) begins
+    TocOutOfBounds {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+
+        /// Length of the table of contents
+        len: u64,
+
+        /// The position where the table of contents should end
+        expected_end: u64,
+    },
+
+    /// The table of contents ends in the middle of an entry
+    TruncatedToc {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+
+        /// Length of the table of contents
+        len: u64,
+    },
+
+    /// The table of contents is followed by unexpected bytes
+    TocLengthMismatch {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+
+        /// Length of the table of contents as defined in the trailer
+        expected: u64,
+
+        /// Number of bytes actually used by the table of contents
+        got: u64,
+    },
+
+    /// The table of contents exceeds [`ReaderOptions::max_toc_size`](crate::ReaderOptions::max_toc_size)
+    TocTooLarge {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+
+        /// Length of the table of contents
+        len: u64,
+
+        /// The configured limit
+        limit: u64,
+    },
+
+    /// The table of contents exceeds [`ReaderOptions::max_entry_count`](crate::ReaderOptions::max_entry_count)
+    TooManySections {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents
+        offset: u64,
+
+        /// Number of entries in the table of contents
+        count: u32,
+
+        /// The configured limit
+        limit: u32,
+    },
+
+    /// A section name exceeds [`ReaderOptions::max_name_len`](crate::ReaderOptions::max_name_len)
+    SectionNameTooLong {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the table of contents entry
+        offset: u64,
+
+        /// Length of the section name
+        len: usize,
+
+        /// The configured limit
+        limit: u16,
+    },
+
+    /// A section does not lie before the table of contents
+    SectionOutOfBounds {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The section name
+        section: SectionName,
+
+        /// Position of the section
+        offset: u64,
+
+        /// Length of the section
+        len: u64,
+
+        /// Position of the table of contents
+        toc_pos: u64,
+    },
 
     /// Checksum mismatch
     ChecksumMismatch {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The section name, or `None` for the table of contents
+        section: Option<SectionName>,
+
+        /// Position of the section (or table of contents)
+        offset: u64,
+
         /// The calculated checksum
-        got: Checksum,
+        got: Box<Checksum>,
 
         /// The expected checksum as defined in the file format
-        expected: Checksum,
+        expected: Box<Checksum>,
+    },
+
+    /// A block of a section does not match its block checksum
+    /// (see [`Writer::use_block_checksums`](crate::Writer::use_block_checksums))
+    BlockChecksumMismatch {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The section name
+        section: SectionName,
+
+        /// Index of the block in the section
+        block: u64,
+
+        /// Position of the block
+        offset: u64,
+
+        /// The calculated checksum
+        got: Box<Checksum>,
+
+        /// The expected checksum as defined in the block index
+        expected: Box<Checksum>,
+    },
+
+    /// The block index is missing or corrupted
+    InvalidBlockIndex {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the block index (or its footer)
+        offset: u64,
+    },
+
+    /// The block index exceeds [`ReaderOptions::max_block_index_size`](crate::ReaderOptions::max_block_index_size)
+    BlockIndexTooLarge {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the block index footer
+        offset: u64,
+
+        /// Length of the block index as defined in its footer
+        len: u64,
+
+        /// The configured limit
+        limit: u64,
+    },
+
+    /// The parity region is missing or corrupted, or does not match the table of contents
+    /// (see [`Writer::use_parity`](crate::Writer::use_parity))
+    InvalidParity {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// Position of the parity region (or its footer)
+        offset: u64,
+    },
+
+    /// The parity layout passed to [`Writer::use_parity`](crate::Writer::use_parity)
+    /// cannot be used to encode stripes
+    InvalidParityLayout {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The shard size in bytes
+        shard_size: u32,
+
+        /// The number of data shards per stripe
+        data_shards: u8,
+
+        /// The number of parity shards per stripe
+        parity_shards: u8,
+    },
+
+    /// The Merkle root in the trailer does not match the table of contents
+    /// (see [`Writer::use_merkle_root`](crate::Writer::use_merkle_root))
+    MerkleRootMismatch {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The Merkle root calculated from the table of contents,
+        /// or `None` if the sections have no checksums
+        got: Option<Box<Checksum>>,
+
+        /// The Merkle root stored in the trailer
+        expected: Box<Checksum>,
+    },
+
+    /// One or more sections failed verification (see [`Reader::verify_parallel`](crate::Reader::verify_parallel))
+    CorruptedSections {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The errors of all failed sections, in the order of the table of contents
+        errors: Vec<Self>,
+    },
+
+    /// One or more archives of a [`SyncGroup`](crate::SyncGroup) could not be committed
+    /// (see [`SyncGroup::commit`](crate::SyncGroup::commit))
+    CommitFailed {
+        /// The errors of all failed archives and directories
+        errors: Vec<Self>,
+    },
+
+    /// A section name occurs in more than one of the archives being merged
+    /// (see [`ConflictPolicy::Error`](crate::ConflictPolicy::Error))
+    DuplicateSectionName {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The section name
+        section: SectionName,
+    },
+
+    /// The archive is not signed
+    #[cfg(feature = "signing")]
+    MissingSignature {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+    },
+
+    /// The archive signature does not match any of the trusted public keys
+    #[cfg(feature = "signing")]
+    InvalidSignature {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+    },
+
+    /// The archive is (to be) signed, but uses a checksum type that is not cryptographic
+    /// (see [`crate::ChecksumType::is_cryptographic`])
+    #[cfg(feature = "signing")]
+    InsecureChecksumType {
+        /// Path of the archive, if known
+        path: Option<PathBuf>,
+
+        /// The checksum type of the archive
+        checksum_type: crate::ChecksumType,
     },
 }
 
+impl Error {
+    /// Returns the path of the archive the error occurred in, if known.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Io(_) | Self::CommitFailed { .. } => None,
+            Self::FileIo { path, .. } => Some(path),
+            Self::IncompleteArchive { path, .. }
+            | Self::TruncatedArchive { path, .. }
+            | Self::InvalidTrailerMagic { path, .. }
+            | Self::UnsupportedVersion { path, .. }
+            | Self::UnsupportedChecksumType { path, .. }
+            | Self::TrailerChecksumMismatch { path, .. }
+            | Self::InvalidTocMagic { path, .. }
+            | Self::TocOutOfBounds { path, .. }
+            | Self::TruncatedToc { path, .. }
+            | Self::TocLengthMismatch { path, .. }
+            | Self::TocTooLarge { path, .. }
+            | Self::TooManySections { path, .. }
+            | Self::SectionNameTooLong { path, .. }
+            | Self::SectionOutOfBounds { path, .. }
+            | Self::ChecksumMismatch { path, .. }
+            | Self::BlockChecksumMismatch { path, .. }
+            | Self::InvalidBlockIndex { path, .. }
+            | Self::BlockIndexTooLarge { path, .. }
+            | Self::InvalidParity { path, .. }
+            | Self::InvalidParityLayout { path, .. }
+            | Self::MerkleRootMismatch { path, .. }
+            | Self::CorruptedSections { path, .. }
+            | Self::DuplicateSectionName { path, .. } => path.as_deref(),
+
+            #[cfg(feature = "signing")]
+            Self::MissingSignature { path }
+            | Self::InvalidSignature { path }
+            | Self::InsecureChecksumType { path, .. } => path.as_deref(),
+        }
+    }
+
+    /// Returns the underlying IO error, if any.
+    #[must_use]
+    pub fn io_error(&self) -> Option<&std::io::Error> {
+        match self {
+            Self::Io(inner) | Self::FileIo { inner, .. } => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Wraps an IO error that occurred while accessing the file at `path`.
+    pub(crate) fn io_at(inner: std::io::Error, path: &Path) -> Self {
+        Self::FileIo {
+            path: path.to_path_buf(),
+            inner,
+        }
+    }
+
+    /// Sets the path of the archive, if not already set.
+    ///
+    /// IO errors become [`Error::FileIo`].
+    pub(crate) fn with_path(mut self, archive_path: &Path) -> Self {
+        if let Self::Io(inner) = self {
+            return Self::io_at(inner, archive_path);
+        }
+
+        match &mut self {
+            Self::Io(_) | Self::FileIo { .. } | Self::CommitFailed { .. } => {}
+            Self::IncompleteArchive { path, .. }
+            | Self::TruncatedArchive { path, .. }
+            | Self::InvalidTrailerMagic { path, .. }
+            | Self::UnsupportedVersion { path, .. }
+            | Self::UnsupportedChecksumType { path, .. }
+            | Self::TrailerChecksumMismatch { path, .. }
+            | Self::InvalidTocMagic { path, .. }
+            | Self::TocOutOfBounds { path, .. }
+            | Self::TruncatedToc { path, .. }
+            | Self::TocLengthMismatch { path, .. }
+            | Self::TocTooLarge { path, .. }
+            | Self::TooManySections { path, .. }
+            | Self::SectionNameTooLong { path, .. }
+            | Self::SectionOutOfBounds { path, .. }
+            | Self::ChecksumMismatch { path, .. }
+            | Self::BlockChecksumMismatch { path, .. }
+            | Self::InvalidBlockIndex { path, .. }
+            | Self::BlockIndexTooLarge { path, .. }
+            | Self::InvalidParity { path, .. }
+            | Self::InvalidParityLayout { path, .. }
+            | Self::MerkleRootMismatch { path, .. }
+            | Self::CorruptedSections { path, .. }
+            | Self::DuplicateSectionName { path, .. } => {
+                path.get_or_insert_with(|| archive_path.to_path_buf());
+            }
+
+            #[cfg(feature = "signing")]
+            Self::MissingSignature { path }
+            | Self::InvalidSignature { path }
+            | Self::InsecureChecksumType { path, .. } => {
+                path.get_or_insert_with(|| archive_path.to_path_buf());
+            }
+        }
+
+        self
+    }
+}
+
 impl std::fmt::Display for Error {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "SfaError: {self:?}")
+        if let Some(path) = self.path() {
+            write!(f, "{}: ", path.display())?;
+        }
+
+        match self {
+            Self::Io(inner) | Self::FileIo { inner, .. } => write!(f, "I/O error: {inner}"),
+            Self::IncompleteArchive { len, .. } => {
+                write!(f, "incomplete archive: no trailer found in {len} bytes")
+            }
+            Self::TruncatedArchive {
+                len, expected_len, ..
+            } => write!(
+                f,
+                "truncated archive: expected {expected_len} bytes, but found {len}"
+            ),
+            Self::InvalidTrailerMagic { offset, .. } => {
+                write!(f, "invalid trailer magic bytes at offset {offset}")
+            }
+            Self::UnsupportedVersion {
+                offset, version, ..
+            } => write!(
+                f,
+                "unsupported format version {version} in trailer at offset {offset}"
+            ),
+            Self::UnsupportedChecksumType {
+                offset,
+                checksum_type,
+                ..
+            } => write!(
+                f,
+                "unsupported checksum type {checksum_type} at offset {offset}"
+            ),
+            Self::TrailerChecksumMismatch {
+                offset,
+                got,
+                expected,
+                ..
+            } => write!(
+                f,
+                "trailer checksum mismatch at offset {offset}: expected {expected:016x}, got {got:016x}"
+            ),
+            Self::InvalidTocMagic { offset, .. } => {
+                write!(
+                    f,
+                    "invalid table of contents magic bytes at offset {offset}"
+                )
+            }
+            Self::TocOutOfBounds {
+                offset,
+                len,
+                expected_end,
+                ..
+            } => write!(
+                f,
+                "table of contents at offset {offset} with length {len} does not end at offset {expected_end}"
+            ),
+            Self::TruncatedToc { offset, len, .. } => write!(
+                f,
+                "table of contents at offset {offset} with length {len} is truncated"
+            ),
+            Self::TocLengthMismatch {
+                offset,
+                expected,
+                got,
+                ..
+            } => write!(
+                f,
+                "table of contents at offset {offset} should be {expected} bytes long, but is {got} bytes long"
+            ),
+            Self::TocTooLarge {
+                offset, len, limit, ..
+            } => write!(
+                f,
+                "table of contents at offset {offset} with length {len} exceeds limit of {limit} bytes"
+            ),
+            Self::TooManySections {
+                offset,
+                count,
+                limit,
+                ..
+            } => write!(
+                f,
+                "table of contents at offset {offset} with {count} entries exceeds limit of {limit} entries"
+            ),
+            Self::SectionNameTooLong {
+                offset, len, limit, ..
+            } => write!(
+                f,
+                "section name at offset {offset} with length {len} exceeds limit of {limit} bytes"
+            ),
+            Self::SectionOutOfBounds {
+                section,
+                offset,
+                len,
+                toc_pos,
+                ..
+            } => write!(
+                f,
+                "section {:?} at offset {offset} with length {len} exceeds table of contents at offset {toc_pos}",
+                String::from_utf8_lossy(section),
+            ),
+            Self::ChecksumMismatch {
+                section,
+                offset,
+                got,
+                expected,
+                ..
+            } => {
+                match section {
+                    Some(section) => write!(
+                        f,
+                        "checksum mismatch in section {:?} at offset {offset}",
+                        String::from_utf8_lossy(section),
+                    )?,
+                    None => write!(
+                        f,
+                        "checksum mismatch in table of contents at offset {offset}"
+                    )?,
+                }
+
+                write!(f, ": expected {expected}, got {got}")
+            }
+            Self::BlockChecksumMismatch {
+                section,
+                block,
+                offset,
+                got,
+                expected,
+                ..
+            } => write!(
+                f,
+                "checksum mismatch in block {block} of section {:?} at offset {offset}: expected {expected}, got {got}",
+                String::from_utf8_lossy(section),
+            ),
+            Self::InvalidBlockIndex { offset, .. } => {
+                write!(f, "invalid block index at offset {offset}")
+            }
+            Self::BlockIndexTooLarge {
+                offset, len, limit, ..
+            } => write!(
+                f,
+                "block index at offset {offset} with length {len} exceeds limit of {limit} bytes"
+            ),
+            Self::InvalidParity { offset, .. } => {
+                write!(f, "invalid parity region at offset {offset}")
+            }
+            Self::InvalidParityLayout {
+                shard_size,
+                data_shards,
+                parity_shards,
+                ..
+            } => write!(
+                f,
+                "invalid parity layout: {data_shards} data and {parity_shards} parity shards of {shard_size} bytes",
+            ),
+            Self::MerkleRootMismatch { got, expected, .. } => match got {
+                Some(got) => write!(
+                    f,
+                    "Merkle root mismatch: expected {expected}, got {got}"
+                ),
+                None => write!(
+                    f,
+                    "Merkle root mismatch: expected {expected}, but sections have no checksums"
+                ),
+            },
+            Self::CorruptedSections { errors, .. } => {
+                write!(f, "{} sections failed verification", errors.len())?;
+
+                for (idx, error) in errors.iter().enumerate() {
+                    write!(f, "{} {error}", if idx == 0 { ':' } else { ';' })?;
+                }
+
+                Ok(())
+            }
+            Self::CommitFailed { errors } => {
+                write!(f, "{} archives failed to commit", errors.len())?;
+
+                for (idx, error) in errors.iter().enumerate() {
+                    write!(f, "{} {error}", if idx == 0 { ':' } else { ';' })?;
+                }
+
+                Ok(())
+            }
+            Self::DuplicateSectionName { section, .. } => write!(
+                f,
+                "section {:?} also occurs in another archive being merged",
+                String::from_utf8_lossy(section),
+            ),
+
+            #[cfg(feature = "signing")]
+            Self::MissingSignature { .. } => write!(f, "archive is not signed"),
+
+            #[cfg(feature = "signing")]
+            Self::InvalidSignature { .. } => {
+                write!(f, "archive signature does not match any trusted key")
+            }
+
+            #[cfg(feature = "signing")]
+            Self::InsecureChecksumType { checksum_type, .. } => write!(
+                f,
+                "signed archives require a cryptographic checksum type, got {checksum_type:?}",
+            ),
+        }
     }
 }
 
@@ -44,7 +690,7 @@ impl From<std::io::Error> for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::Io(inner) => Some(inner),
+            Self::Io(inner) | Self::FileIo { inner, .. } => Some(inner),
             _ => None,
         }
     }
@@ -0,0 +1,80 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Copying between files inside the kernel, without moving the data through userspace
+
+use std::fs::File;
+
+/// Copies up to `len` bytes from `src` (starting at `offset`) to the current position of `dst`,
+/// advancing the position of `dst`.
+///
+/// Returns the number of bytes copied, which is `0` if `offset` is at (or past) the end of `src`.
+#[cfg(target_os = "linux")]
+pub fn copy_file_range(src: &File, offset: u64, dst: &File, len: usize) -> std::io::Result<usize> {
+    use std::os::fd::AsRawFd;
+
+    let mut off_in = libc::loff_t::try_from(offset)
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+
+    loop {
+        // SAFETY: Both file descriptors are valid for the duration of the call,
+        // `off_in` is a valid pointer, and a null output offset uses the position of `dst`
+        let n = unsafe {
+            libc::copy_file_range(
+                src.as_raw_fd(),
+                &raw mut off_in,
+                dst.as_raw_fd(),
+                std::ptr::null_mut(),
+                len,
+                0,
+            )
+        };
+
+        if let Ok(n) = usize::try_from(n) {
+            return Ok(n);
+        }
+
+        let e = std::io::Error::last_os_error();
+        if e.kind() != std::io::ErrorKind::Interrupted {
+            return Err(e);
+        }
+    }
+}
+
+/// Copies up to `len` bytes from `src` (starting at `offset`) to the current position of `dst`.
+///
+/// Not supported on this platform, so callers fall back to copying through userspace.
+#[cfg(not(target_os = "linux"))]
+pub fn copy_file_range(
+    _src: &File,
+    _offset: u64,
+    _dst: &File,
+    _len: usize,
+) -> std::io::Result<usize> {
+    Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+}
+
+/// Returns `true` if the error means the copy cannot be done in the kernel for these files
+/// (e.g. different file systems on old kernels, or unsupported file types),
+/// so it should be done through userspace instead.
+pub fn is_unsupported(e: &std::io::Error) -> bool {
+    if e.kind() == std::io::ErrorKind::Unsupported {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(code) = e.raw_os_error() {
+        return matches!(
+            code,
+            libc::EXDEV
+                | libc::ENOSYS
+                | libc::EOPNOTSUPP
+                | libc::EINVAL
+                | libc::EBADF
+                | libc::EPERM
+        );
+    }
+
+    false
+}
@@ -0,0 +1,58 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{Checksum, Toc};
+
+/// Summary of an archive returned by [`Writer::finish`](crate::Writer::finish)
+///
+/// Contains everything needed to register the archive (e.g. in a catalog)
+/// without reopening it.
+pub struct FinishedArchive {
+    pub(crate) toc: Toc,
+    pub(crate) file_size: u64,
+    pub(crate) toc_pos: u64,
+    pub(crate) toc_len: u64,
+    pub(crate) checksum: Checksum,
+    pub(crate) merkle_root: Option<Checksum>,
+}
+
+impl FinishedArchive {
+    /// Returns the table of contents.
+    ///
+    /// The entries contain the section checksums.
+    #[must_use]
+    pub fn toc(&self) -> &Toc {
+        &self.toc
+    }
+
+    /// Returns the total file size in bytes.
+    #[must_use]
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Returns the position of the table of contents.
+    #[must_use]
+    pub fn toc_pos(&self) -> u64 {
+        self.toc_pos
+    }
+
+    /// Returns the length of the table of contents in bytes.
+    #[must_use]
+    pub fn toc_len(&self) -> u64 {
+        self.toc_len
+    }
+
+    /// Returns the full-file checksum.
+    #[must_use]
+    pub fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    /// Returns the Merkle root over the sections, if enabled (see [`Writer::use_merkle_root`](crate::Writer::use_merkle_root)).
+    #[must_use]
+    pub fn merkle_root(&self) -> Option<Checksum> {
+        self.merkle_root
+    }
+}
@@ -13,10 +13,9 @@
 //! # let path = dir.path().join("hello.sfa");
 //!
 //! let mut writer = Writer::new_at_path(&path)?;
-//! writer.start("Section 1")?;
-//! writer.write_all(b"Hello world!\n")?;
-//! let _file_checksum = writer.finish()?;
-//! // If on Unix, you probably want to fsync the directory here
+//! writer.section("Section 1")?.write_all(b"Hello world!\n")?;
+//! let _archive = writer.finish()?;
+//! // If on Unix, you probably want to fsync the directory here (or use `Writer::new_atomic`)
 //!
 //! let reader = Reader::new(&path)?;
 //! let toc = reader.toc();
@@ -42,18 +41,56 @@
 #![allow(clippy::option_if_let_else)]
 #![warn(clippy::redundant_feature_names)]
 
+mod atomic;
+mod block_index;
 mod checksum;
+mod checksum_reader;
 mod checksum_writer;
+mod durability;
 mod error;
+mod file_copy;
+mod finished_archive;
+mod marker;
+mod merge;
+mod merkle;
+mod parallel_writer;
+mod parity;
 mod reader;
+mod reader_options;
+mod repair;
+mod section_writer;
+
+#[cfg(feature = "signing")]
+mod signature;
+
+mod source;
+
+mod tail_reader;
 mod toc;
 mod trailer;
+mod validate;
+mod verified_reader;
 mod writer;
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
-pub use checksum::Checksum;
+pub use atomic::OverwritePolicy;
+pub use checksum::{Checksum, ChecksumType};
+pub use durability::{Durability, SyncGroup};
 pub use error::Error;
+pub use finished_archive::FinishedArchive;
+pub use merge::{ConflictPolicy, Merger};
+pub use merkle::{verify_proof, InclusionProof};
+pub use parallel_writer::{ParallelSection, ParallelWriter, SectionOrder};
 pub use reader::Reader;
+pub use reader_options::ReaderOptions;
+pub use repair::{DamagedRegion, RepairReport};
+pub use section_writer::SectionWriter;
+pub use source::{RandomAccessSource, SourceReader};
 pub use toc::{entry::TocEntry, Toc};
-pub use writer::Writer;
+pub use validate::{ValidationIssue, ValidationReport};
+pub use verified_reader::VerifiedReader;
+pub use writer::{DropBehavior, Writer};
+
+#[cfg(feature = "signing")]
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
@@ -0,0 +1,192 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Section markers
+//!
+//! If enabled, a marker is written directly after each section (so before the next one).
+//! It repeats the section's table of contents entry, so the sections
+//! can be recovered by scanning the file, even if the table of contents or trailer
+//! is missing or corrupted.
+//!
+//! Only sections that were closed (have a marker) are considered complete.
+//!
+//! Each marker ends with an XXH3 (64-bit) checksum of its fields, so a corrupted marker
+//! is skipped, and the scan continues with the next intact marker.
+
+use crate::{checksum_reader::ChecksummedReader, toc::entry::TocEntry, ChecksumType};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+};
+
+pub const MARKER_MAGIC: &[u8] = b"SEC!";
+
+const SCAN_CHUNK_SIZE: usize = 64_000;
+
+pub fn write_into(mut writer: impl Write, entry: &TocEntry) -> std::io::Result<()> {
+    #[allow(clippy::expect_used)]
+    let checksum_type = entry
+        .checksum
+        .as_ref()
+        .expect("section checksum should be set by writer")
+        .checksum_type();
+
+    let mut buf = vec![];
+    buf.write_all(MARKER_MAGIC)?;
+    buf.write_u8(checksum_type.into())?;
+    entry.write_into(&mut buf)?;
+
+    writer.write_all(&buf)?;
+    writer.write_u64::<byteorder::LE>(xxhash_rust::xxh3::xxh3_64(&buf))
+}
+
+/// Returns the size of the marker written after the section of the given entry.
+pub fn size(entry: &TocEntry) -> u64 {
+    let digest_len = entry
+        .checksum
+        .map_or(0, |checksum| checksum.checksum_type().digest_len());
+
+    (MARKER_MAGIC.len() + 1 + 8 + 8 + 2 + entry.name.len() + digest_len + 8) as u64
+}
+
+/// Tries to parse the marker at `offset` (excluding the magic bytes).
+///
+/// Returns error, if the marker checksum does not match.
+pub fn read_from(reader: &mut impl Read, offset: u64) -> crate::Result<TocEntry> {
+    let checksum_type = reader.read_u8()?;
+
+    let Some(checksum_type) = ChecksumType::from_id(checksum_type) else {
+        return Err(crate::Error::UnsupportedChecksumType {
+            path: None,
+            offset,
+            checksum_type,
+        });
+    };
+
+    let entry = TocEntry::read_from_file(reader, Some(checksum_type))?;
+    let expected = reader.read_u64::<byteorder::LE>()?;
+
+    // NOTE: The entry is serialized the same way it was read
+    let mut buf = vec![];
+    buf.write_all(MARKER_MAGIC)?;
+    buf.write_u8(checksum_type.into())?;
+    entry.write_into(&mut buf)?;
+
+    if xxhash_rust::xxh3::xxh3_64(&buf) != expected {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("section marker checksum mismatch at {offset}"),
+        )
+        .into());
+    }
+
+    Ok(entry)
+}
+
+/// Returns the offsets of all occurrences of the given magic bytes.
+pub fn find_candidates(reader: &mut impl Read, magic: &[u8]) -> std::io::Result<Vec<u64>> {
+    let mut candidates = vec![];
+
+    let carry_len = magic.len() - 1;
+    let mut buf = vec![0; SCAN_CHUNK_SIZE + carry_len];
+
+    // Bytes at the start of the buffer that are carried over from the previous chunk
+    let mut carry = 0;
+
+    // File offset of the start of the buffer
+    let mut base = 0;
+
+    loop {
+        #[allow(clippy::indexing_slicing)]
+        let n = reader.read(&mut buf[carry..])?;
+
+        if n == 0 {
+            break;
+        }
+
+        let filled = carry + n;
+
+        #[allow(clippy::indexing_slicing)]
+        for (idx, window) in buf[..filled].windows(magic.len()).enumerate() {
+            if window == magic {
+                candidates.push(base + idx as u64);
+            }
+        }
+
+        carry = filled.min(carry_len);
+        buf.copy_within(filled - carry..filled, 0);
+        base += (filled - carry) as u64;
+    }
+
+    Ok(candidates)
+}
+
+/// Scans the file for section markers, returning the checksum type and all intact sections.
+///
+/// Sections that fail their checksum are skipped. If a marker is corrupted,
+/// its section is skipped, and the scan continues with the next intact marker
+/// that points to the bytes before it.
+pub fn scan(file: &mut File) -> crate::Result<(Option<ChecksumType>, Vec<TocEntry>)> {
+    log::debug!("Scanning file for section markers");
+
+    file.seek(SeekFrom::Start(0))?;
+    let candidates = find_candidates(&mut BufReader::new(&mut *file), MARKER_MAGIC)?;
+
+    let mut checksum_type = None;
+    let mut entries = vec![];
+    let mut section_start = 0;
+
+    for candidate in candidates {
+        if candidate < section_start {
+            // Inside the previous marker
+            continue;
+        }
+
+        file.seek(SeekFrom::Start(candidate + MARKER_MAGIC.len() as u64))?;
+
+        let mut reader = BufReader::new(&mut *file);
+        let Ok(entry) = read_from(&mut reader, candidate) else {
+            continue;
+        };
+        let marker_end = reader.stream_position()?;
+
+        // NOTE: The section may start after the end of the previous intact marker,
+        // if the markers in between are corrupted
+        if entry.pos < section_start || entry.pos.checked_add(entry.len) != Some(candidate) {
+            continue;
+        }
+
+        // NOTE: The marker is valid, so the next section starts after it,
+        // even if this section turns out to be corrupted
+        section_start = marker_end;
+
+        let Some(expected) = entry.checksum else {
+            continue;
+        };
+
+        file.seek(SeekFrom::Start(entry.pos))?;
+        let mut reader = ChecksummedReader::new(
+            BufReader::new(&mut *file).take(entry.len),
+            expected.checksum_type(),
+        );
+        std::io::copy(&mut reader, &mut std::io::sink())?;
+
+        let got = reader.checksum();
+
+        if got == expected {
+            log::trace!("Recovered section {:?} at {}", entry.name, entry.pos);
+            checksum_type = Some(expected.checksum_type());
+            entries.push(entry);
+        } else {
+            log::warn!(
+                "Section {:?} at {} is corrupted: expected {expected:?}, got {got:?}",
+                entry.name,
+                entry.pos,
+            );
+        }
+    }
+
+    Ok((checksum_type, entries))
+}
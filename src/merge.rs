@@ -0,0 +1,249 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::entry::SectionName, FinishedArchive, Reader, Writer};
+use std::{collections::HashMap, path::PathBuf};
+
+/// What to do if a section name occurs in more than one of the archives being merged
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Fail with [`Error::DuplicateSectionName`](crate::Error::DuplicateSectionName),
+    /// before any section is written
+    #[default]
+    Error,
+
+    /// Prefix the conflicting sections with the file name of their archive and `/`
+    /// (e.g. `worker-1.sfa/results`)
+    ///
+    /// If archives in different directories have the same file name, their position
+    /// in the merge order is appended to it (e.g. `results.sfa~1/results`).
+    /// If the prefixed names still conflict, fails with
+    /// [`Error::DuplicateSectionName`](crate::Error::DuplicateSectionName).
+    Prefix,
+
+    /// Only keep the sections of the last archive the name occurs in
+    KeepLast,
+}
+
+/// Merges multiple archives into one
+///
+/// The sections are copied (see [`Writer::copy_section_from`]) in the order
+/// the archives were added, keeping their order within each archive.
+/// Aliases (see [`Writer::alias`]) stay aliases of the copied section they lie in,
+/// so shared bytes are not duplicated.
+///
+/// Section names are only compared between archives, so duplicate names
+/// within a single archive are kept as they are.
+///
+/// ```
+/// use sfa::{ConflictPolicy, Merger, Reader, Writer};
+/// use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+///
+/// for worker in 0..3 {
+///     let mut writer = Writer::new_at_path(dir.path().join(format!("worker-{worker}.sfa")))?;
+///     writer.section("results")?.write_all(b"Hello world!\n")?;
+///     writer.finish()?;
+/// }
+///
+/// let path = dir.path().join("job.sfa");
+///
+/// Merger::new()
+///     .add_archive(dir.path().join("worker-0.sfa"))?
+///     .add_archive(dir.path().join("worker-1.sfa"))?
+///     .add_archive(dir.path().join("worker-2.sfa"))?
+///     .use_conflict_policy(ConflictPolicy::Prefix)
+///     .merge_into(Writer::new_at_path(&path)?)?;
+///
+/// let reader = Reader::new(&path)?;
+/// assert_eq!(3, reader.toc().len());
+/// assert!(reader.toc().section(b"worker-1.sfa/results").is_some());
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+#[derive(Default)]
+pub struct Merger {
+    sources: Vec<(PathBuf, Reader)>,
+    conflict_policy: ConflictPolicy,
+}
+
+impl Merger {
+    /// Creates a new merger without any archives.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the archive at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive cannot be read.
+    pub fn add_archive(self, path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+        let reader = Reader::new(&path)?;
+        Ok(self.add_reader(path, reader))
+    }
+
+    /// Adds an archive that was already opened, e.g. using [`Reader::with_options`]
+    /// or [`Reader::salvage`].
+    ///
+    /// The sections are copied from the archive at `path`.
+    #[must_use]
+    pub fn add_reader(mut self, path: impl Into<PathBuf>, reader: Reader) -> Self {
+        self.sources.push((path.into(), reader));
+        self
+    }
+
+    /// Sets what to do if a section name occurs in more than one archive.
+    ///
+    /// Defaults to [`ConflictPolicy::Error`].
+    #[must_use]
+    pub fn use_conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Writes the sections of all archives into `writer`, and finishes it.
+    ///
+    /// Returns a summary of the merged archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or a section name occurs in more than one archive
+    /// (using [`ConflictPolicy::Error`]).
+    /// In that case, the writer is dropped without being finished (see [`DropBehavior`](crate::DropBehavior)).
+    pub fn merge_into(self, mut writer: Writer) -> crate::Result<FinishedArchive> {
+        // NOTE: Maps each section name to the first and last archive it occurs in
+        let mut occurrences: HashMap<&[u8], (usize, usize)> = HashMap::new();
+
+        for (idx, (path, reader)) in self.sources.iter().enumerate() {
+            for entry in reader.toc().iter() {
+                let (first, last) = occurrences.entry(entry.name()).or_insert((idx, idx));
+                *last = idx;
+
+                if *first != idx && self.conflict_policy == ConflictPolicy::Error {
+                    log::error!("Section name occurs in more than one archive");
+                    return Err(crate::Error::DuplicateSectionName {
+                        path: Some(path.clone()),
+                        section: entry.name().to_vec(),
+                    });
+                }
+            }
+        }
+
+        let prefixed_names = if self.conflict_policy == ConflictPolicy::Prefix {
+            self.prefixed_names(&occurrences)?
+        } else {
+            HashMap::new()
+        };
+
+        log::debug!("Merging {} archives", self.sources.len());
+
+        for (idx, (path, reader)) in self.sources.iter().enumerate() {
+            // NOTE: Original and new position of each section that was copied from this archive
+            let mut copied: Vec<(u64, u64, u64)> = Vec::new();
+
+            for (entry_idx, entry) in reader.toc().iter().enumerate() {
+                let conflict = occurrences
+                    .get(entry.name())
+                    .filter(|(first, last)| first != last);
+
+                if let (ConflictPolicy::KeepLast, Some((_, last))) =
+                    (self.conflict_policy, conflict)
+                {
+                    if *last != idx {
+                        log::trace!("Skipping section that occurs in a later archive");
+                        continue;
+                    }
+                }
+
+                let name = prefixed_names
+                    .get(&(idx, entry_idx))
+                    .map_or_else(|| entry.name().to_vec(), Clone::clone);
+
+                // NOTE: Aliases stay aliases if the section they lie in was copied,
+                // otherwise their bytes are copied
+                let target = entry
+                    .is_alias()
+                    .then(|| {
+                        copied.iter().find(|(pos, _, len)| {
+                            *pos <= entry.pos() && entry.pos() + entry.len() <= pos + len
+                        })
+                    })
+                    .flatten();
+
+                if let Some((pos, new_pos, _)) = target {
+                    writer.alias_at(name, new_pos + (entry.pos() - pos), entry.len())?;
+                } else {
+                    let new_entry = writer.copy_section_as(path, entry, name)?;
+                    copied.push((entry.pos(), new_entry.pos(), entry.len()));
+                }
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Returns the prefixed names of the conflicting sections, by archive and entry index,
+    /// making sure they do not conflict with any other section.
+    fn prefixed_names(
+        &self,
+        occurrences: &HashMap<&[u8], (usize, usize)>,
+    ) -> crate::Result<HashMap<(usize, usize), SectionName>> {
+        let file_names = self
+            .sources
+            .iter()
+            .map(|(path, _)| {
+                path.file_name()
+                    .map(|name| name.as_encoded_bytes().to_vec())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut prefixed_names = HashMap::new();
+
+        // NOTE: Maps each resulting section name to the archive it occurs in
+        let mut names: HashMap<SectionName, usize> = HashMap::new();
+
+        for (idx, (path, reader)) in self.sources.iter().enumerate() {
+            for (entry_idx, entry) in reader.toc().iter().enumerate() {
+                let conflict = occurrences
+                    .get(entry.name())
+                    .is_some_and(|(first, last)| first != last);
+
+                let name = if conflict {
+                    let file_name = file_names.get(idx).map(Vec::as_slice).unwrap_or_default();
+
+                    let mut name = file_name.to_vec();
+                    if file_names
+                        .iter()
+                        .filter(|other| *other == file_name)
+                        .count()
+                        > 1
+                    {
+                        name.extend_from_slice(format!("~{idx}").as_bytes());
+                    }
+                    name.push(b'/');
+                    name.extend_from_slice(entry.name());
+
+                    prefixed_names.insert((idx, entry_idx), name.clone());
+                    name
+                } else {
+                    entry.name().to_vec()
+                };
+
+                if *names.entry(name.clone()).or_insert(idx) != idx {
+                    log::error!("Prefixed section name occurs in more than one archive");
+                    return Err(crate::Error::DuplicateSectionName {
+                        path: Some(path.clone()),
+                        section: name,
+                    });
+                }
+            }
+        }
+
+        Ok(prefixed_names)
+    }
+}
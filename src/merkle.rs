@@ -0,0 +1,269 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Merkle tree over sections
+//!
+//! The leaves are the sections in the order of the table of contents. Each leaf hashes the
+//! section's position, length, name and checksum, and the tree is built like in RFC 6962
+//! (the left subtree of a node always contains the largest power of two of leaves that is
+//! smaller than the number of leaves), using the archive's [`ChecksumType`].
+//! Leaves and nodes are prefixed with different bytes, so a node cannot be passed off as a leaf.
+
+use crate::{
+    checksum::{Hasher, MAX_DIGEST_LEN},
+    toc::entry::TocEntry,
+    Checksum, ChecksumType,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+const LEAF_PREFIX: u8 = 0x0;
+const NODE_PREFIX: u8 = 0x1;
+
+/// Hashes the leaf of a section with the given checksum.
+fn leaf_hash(entry: &TocEntry, digest: &Checksum) -> Checksum {
+    let mut hasher = Hasher::new(digest.checksum_type());
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(&entry.pos.to_le_bytes());
+    hasher.update(&entry.len.to_le_bytes());
+    hasher.update(&(entry.name.len() as u64).to_le_bytes());
+    hasher.update(&entry.name);
+    hasher.update(digest.as_bytes());
+    hasher.checksum()
+}
+
+fn node_hash(left: &Checksum, right: &Checksum) -> Checksum {
+    let mut hasher = Hasher::new(left.checksum_type());
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    hasher.checksum()
+}
+
+/// Returns the largest power of two that is smaller than `n` (which needs to be at least 2).
+fn split_point(n: usize) -> usize {
+    1 << ((n - 1).ilog2())
+}
+
+/// Returns the leaves of the given sections, which need to have checksums.
+pub fn leaves(entries: &[TocEntry]) -> Option<Vec<Checksum>> {
+    entries
+        .iter()
+        .map(|entry| Some(leaf_hash(entry, entry.checksum.as_ref()?)))
+        .collect()
+}
+
+/// Calculates the Merkle root of the given leaves.
+pub fn root(checksum_type: ChecksumType, leaves: &[Checksum]) -> Checksum {
+    match leaves {
+        [] => Hasher::new(checksum_type).checksum(),
+        [leaf] => *leaf,
+        _ => {
+            let (left, right) = leaves.split_at(split_point(leaves.len()));
+            node_hash(&root(checksum_type, left), &root(checksum_type, right))
+        }
+    }
+}
+
+/// Returns the hashes needed to calculate the root from the leaf at `index`, starting at the leaf.
+fn audit_path(checksum_type: ChecksumType, leaves: &[Checksum], index: usize) -> Vec<Checksum> {
+    if leaves.len() <= 1 {
+        return vec![];
+    }
+
+    let (left, right) = leaves.split_at(split_point(leaves.len()));
+
+    if index < left.len() {
+        let mut path = audit_path(checksum_type, left, index);
+        path.push(root(checksum_type, right));
+        path
+    } else {
+        let mut path = audit_path(checksum_type, right, index - left.len());
+        path.push(root(checksum_type, left));
+        path
+    }
+}
+
+/// Proof that a section is part of an archive with a given Merkle root
+///
+/// Created using [`Reader::inclusion_proof`](crate::Reader::inclusion_proof), and checked using
+/// [`verify_proof`]. The proof contains the section's table of contents entry, and can be
+/// serialized to send it along with the section.
+#[derive(Clone, Debug)]
+pub struct InclusionProof {
+    entry: TocEntry,
+
+    /// Position of the section in the table of contents
+    index: u64,
+
+    /// Number of sections in the table of contents
+    leaf_count: u64,
+
+    /// Hashes needed to calculate the root from the section's leaf, starting at the leaf
+    path: Vec<Checksum>,
+}
+
+impl InclusionProof {
+    pub(crate) fn new(entries: &[TocEntry], index: usize) -> Option<Self> {
+        let entry = entries.get(index)?;
+        let checksum_type = entry.checksum?.checksum_type();
+        let leaves = leaves(entries)?;
+
+        Some(Self {
+            entry: entry.clone(),
+            index: index as u64,
+            leaf_count: entries.len() as u64,
+            path: audit_path(checksum_type, &leaves, index),
+        })
+    }
+
+    /// Returns the table of contents entry of the section.
+    #[must_use]
+    pub fn entry(&self) -> &TocEntry {
+        &self.entry
+    }
+
+    /// Serializes the proof.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+
+        #[allow(clippy::expect_used)]
+        self.write_into(&mut buf)
+            .expect("writing into a vector should not fail");
+
+        buf
+    }
+
+    fn write_into(&self, mut writer: impl Write) -> std::io::Result<()> {
+        use byteorder::LE;
+
+        crate::marker::write_into(&mut writer, &self.entry)?;
+        writer.write_u64::<LE>(self.index)?;
+        writer.write_u64::<LE>(self.leaf_count)?;
+
+        // NOTE: The path has at most 64 hashes
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u32::<LE>(self.path.len() as u32)?;
+
+        for hash in &self.path {
+            writer.write_all(hash.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Deserializes a proof created with [`InclusionProof::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the proof is truncated or uses an unsupported checksum type.
+    pub fn from_bytes(mut bytes: &[u8]) -> crate::Result<Self> {
+        use byteorder::LE;
+
+        let reader = &mut bytes;
+
+        let mut magic = [0; crate::marker::MARKER_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+
+        if magic != crate::marker::MARKER_MAGIC {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid inclusion proof magic bytes",
+            )
+            .into());
+        }
+
+        let entry = crate::marker::read_from(reader, 0)?;
+
+        // NOTE: Entries read from a marker always have a checksum
+        let checksum_type = entry
+            .checksum
+            .map_or_else(ChecksumType::default, |checksum| checksum.checksum_type());
+
+        let index = reader.read_u64::<LE>()?;
+        let leaf_count = reader.read_u64::<LE>()?;
+        let path_len = reader.read_u32::<LE>()?;
+
+        // NOTE: The path length is untrusted, a valid path of 2^64 leaves has at most 64 hashes
+        if path_len > 64 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "inclusion proof path is too long",
+            )
+            .into());
+        }
+
+        let path = (0..path_len)
+            .map(|_| {
+                let mut digest = [0; MAX_DIGEST_LEN];
+
+                #[allow(clippy::indexing_slicing)]
+                let digest = &mut digest[..checksum_type.digest_len()];
+
+                reader.read_exact(digest)?;
+                Ok(Checksum::from_bytes(checksum_type, digest))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            entry,
+            index,
+            leaf_count,
+            path,
+        })
+    }
+}
+
+/// Checks that a section with the given table of contents entry and checksum is part of
+/// an archive with the given Merkle root (see [`Writer::use_merkle_root`](crate::Writer::use_merkle_root)).
+///
+/// `digest` should be calculated from the section contents using the archive's
+/// [`ChecksumType`]; the checksum stored in `entry` is not used. Use a cryptographic
+/// checksum type, as the proof is only as strong as the checksum.
+#[must_use]
+pub fn verify_proof(
+    root: Checksum,
+    entry: &TocEntry,
+    digest: Checksum,
+    proof: &InclusionProof,
+) -> bool {
+    if digest.checksum_type() != root.checksum_type()
+        || proof.index >= proof.leaf_count
+        || proof
+            .path
+            .iter()
+            .any(|hash| hash.checksum_type() != root.checksum_type())
+    {
+        return false;
+    }
+
+    // NOTE: Walks up the tree as described in RFC 9162, section 2.1.3.2
+    let mut index = proof.index;
+    let mut last = proof.leaf_count - 1;
+    let mut hash = leaf_hash(entry, &digest);
+
+    for sibling in &proof.path {
+        if last == 0 {
+            return false;
+        }
+
+        if !index.is_multiple_of(2) || index == last {
+            hash = node_hash(sibling, &hash);
+
+            while index.is_multiple_of(2) && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+
+        index >>= 1;
+        last >>= 1;
+    }
+
+    last == 0 && hash == root
+}
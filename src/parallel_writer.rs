@@ -0,0 +1,391 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::entry::SectionName, FinishedArchive, Writer};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+};
+
+/// Default size above which a section is buffered in a temporary file instead of memory
+const DEFAULT_SPILL_THRESHOLD: u64 = 4 * 1_024 * 1_024;
+
+/// Counter to make spill file names unique within the process
+static SPILL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Order in which the sections of a [`ParallelWriter`] are written into the archive
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SectionOrder {
+    /// In the order the sections were created using [`ParallelWriter::section`],
+    /// regardless of when they are finished
+    #[default]
+    Deterministic,
+
+    /// In the order the sections are finished
+    Completion,
+}
+
+/// Temporary file a large section is buffered in, which is removed when dropped
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl SpillFile {
+    fn create(dir: &std::path::Path) -> std::io::Result<Self> {
+        let path = dir.join(format!(
+            ".sfa-spill.{}.{}.tmp",
+            std::process::id(),
+            SPILL_COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+
+        log::trace!("Creating spill file {}", path.display());
+
+        // NOTE: The file is read back when the section is written into the archive
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to remove spill file: {e:?}");
+        }
+    }
+}
+
+/// Buffered contents of a section
+enum Buffer {
+    Memory(Vec<u8>),
+    Spilled(SpillFile),
+}
+
+/// Section that was finished, but not yet written into the archive
+struct Completed {
+    name: SectionName,
+    buffer: Buffer,
+}
+
+struct State {
+    writer: Writer,
+    next_index: usize,
+    next_to_write: usize,
+
+    /// Finished sections waiting for earlier sections (`None` if dropped without finishing)
+    pending: BTreeMap<usize, Option<Completed>>,
+
+    failed: bool,
+}
+
+impl State {
+    fn append(&mut self, section: Completed) -> std::io::Result<()> {
+        let Completed { name, buffer } = section;
+
+        match buffer {
+            Buffer::Memory(data) => {
+                let mut section = self.writer.section(name)?;
+                section.write_all(&data)?;
+                section.finish()?;
+            }
+            Buffer::Spilled(mut spill) => {
+                spill.writer.flush()?;
+                self.writer
+                    .append_section_from_file(name, spill.writer.get_ref())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn submit(
+        &mut self,
+        order: SectionOrder,
+        index: usize,
+        section: Option<Completed>,
+    ) -> std::io::Result<()> {
+        if self.failed {
+            return Err(std::io::Error::other(
+                "archive writer failed to write an earlier section",
+            ));
+        }
+
+        let result = match order {
+            SectionOrder::Completion => section.map_or(Ok(()), |section| self.append(section)),
+            SectionOrder::Deterministic => {
+                self.pending.insert(index, section);
+                self.write_pending()
+            }
+        };
+
+        if result.is_err() {
+            self.failed = true;
+        }
+
+        result
+    }
+
+    /// Writes all pending sections that are not waiting for an earlier section.
+    fn write_pending(&mut self) -> std::io::Result<()> {
+        while let Some(section) = self.pending.remove(&self.next_to_write) {
+            self.next_to_write += 1;
+
+            if let Some(section) = section {
+                self.append(section)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Archive writer that lets multiple threads write sections concurrently
+///
+/// Each section is buffered by its [`ParallelSection`] (in memory, or in a temporary file
+/// once it exceeds the spill threshold), and written into the archive when it is finished.
+///
+/// ```
+/// use sfa::{ParallelWriter, Reader, Writer};
+/// use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+///
+/// let writer = ParallelWriter::new(Writer::new_at_path(&path)?);
+///
+/// std::thread::scope(|scope| {
+///     let workers = (0..4)
+///         .map(|idx| {
+///             let mut section = writer.section(format!("Section {idx}"));
+///
+///             scope.spawn(move || {
+///                 section.write_all(b"Hello world!\n")?;
+///                 section.finish()
+///             })
+///         })
+///         .collect::<Vec<_>>();
+///
+///     for worker in workers {
+///         worker.join().unwrap()?;
+///     }
+///
+///     Ok::<(), std::io::Error>(())
+/// })?;
+///
+/// writer.finish()?;
+///
+/// let reader = Reader::new(&path)?;
+/// assert_eq!(4, reader.toc().len());
+/// assert_eq!(b"Section 0", reader.toc()[0].name());
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+pub struct ParallelWriter {
+    state: Mutex<State>,
+    order: SectionOrder,
+    spill_threshold: u64,
+    spill_dir: PathBuf,
+}
+
+impl ParallelWriter {
+    /// Creates a new parallel writer that writes the sections into `writer`.
+    ///
+    /// Data already written into `writer` stays in front of the parallel sections.
+    #[must_use]
+    pub fn new(writer: Writer) -> Self {
+        let spill_dir = writer
+            .file_path()
+            .and_then(std::path::Path::parent)
+            .map_or_else(std::env::temp_dir, std::path::Path::to_path_buf);
+
+        Self {
+            state: Mutex::new(State {
+                writer,
+                next_index: 0,
+                next_to_write: 0,
+                pending: BTreeMap::new(),
+                failed: false,
+            }),
+            order: SectionOrder::default(),
+            spill_threshold: DEFAULT_SPILL_THRESHOLD,
+            spill_dir,
+        }
+    }
+
+    /// Sets the order in which the sections are written into the archive.
+    ///
+    /// Defaults to [`SectionOrder::Deterministic`].
+    #[must_use]
+    pub fn use_order(mut self, order: SectionOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets the size in bytes above which a section is buffered in a temporary file instead of memory.
+    ///
+    /// Defaults to 4 MiB.
+    #[must_use]
+    pub fn use_spill_threshold(mut self, bytes: u64) -> Self {
+        self.spill_threshold = bytes;
+        self
+    }
+
+    /// Sets the directory temporary files are created in.
+    ///
+    /// Defaults to the directory of the archive, if known, or the system's temporary directory.
+    #[must_use]
+    pub fn use_spill_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spill_dir = path.into();
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Creates a new section, which can be written to from another thread.
+    ///
+    /// The section is written into the archive when it is finished.
+    /// If it is dropped without being finished, it is left out of the archive.
+    pub fn section(&self, name: impl Into<SectionName>) -> ParallelSection<'_> {
+        let index = {
+            let mut state = self.lock();
+            let index = state.next_index;
+            state.next_index += 1;
+            index
+        };
+
+        ParallelSection {
+            parent: self,
+            index,
+            name: Some(name.into()),
+            buffer: Buffer::Memory(Vec::new()),
+            len: 0,
+        }
+    }
+
+    /// Finishes the archive, see [`Writer::finish`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, including while writing a section.
+    pub fn finish(self) -> crate::Result<FinishedArchive> {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if state.failed {
+            return Err(std::io::Error::other("archive writer failed to write a section").into());
+        }
+
+        state.writer.finish()
+    }
+}
+
+/// Section of a [`ParallelWriter`], which buffers its contents until it is finished
+pub struct ParallelSection<'a> {
+    parent: &'a ParallelWriter,
+    index: usize,
+
+    /// Name of the section (`None` once submitted)
+    name: Option<SectionName>,
+
+    buffer: Buffer,
+    len: u64,
+}
+
+impl ParallelSection<'_> {
+    /// Finishes the section, writing it into the archive.
+    ///
+    /// Using [`SectionOrder::Deterministic`], the section may also need to wait for earlier sections
+    /// to be finished, in which case it is written by the thread that finishes the last of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        let Some(name) = self.name.take() else {
+            return Ok(());
+        };
+
+        let buffer = std::mem::replace(&mut self.buffer, Buffer::Memory(Vec::new()));
+
+        self.parent.lock().submit(
+            self.parent.order,
+            self.index,
+            Some(Completed { name, buffer }),
+        )
+    }
+
+    /// Moves the buffered contents into a temporary file.
+    fn spill(&mut self) -> std::io::Result<()> {
+        let Buffer::Memory(data) = &self.buffer else {
+            return Ok(());
+        };
+
+        let mut spill = SpillFile::create(&self.parent.spill_dir)?;
+        spill.writer.write_all(data)?;
+        self.buffer = Buffer::Spilled(spill);
+
+        Ok(())
+    }
+}
+
+impl Write for ParallelSection<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.len + buf.len() as u64 > self.parent.spill_threshold {
+            self.spill()?;
+        }
+
+        let n = match &mut self.buffer {
+            Buffer::Memory(data) => {
+                data.extend_from_slice(buf);
+                buf.len()
+            }
+            Buffer::Spilled(spill) => spill.writer.write(buf)?,
+        };
+
+        self.len += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for ParallelSection<'_> {
+    fn drop(&mut self) {
+        if self.name.take().is_none() {
+            return;
+        }
+
+        log::debug!("Section was dropped without finishing, leaving it out of the archive");
+
+        // NOTE: Later sections must not wait for this one
+        let result = self
+            .parent
+            .lock()
+            .submit(self.parent.order, self.index, None);
+
+        if let Err(e) = result {
+            log::error!("Failed to write pending sections: {e:?}");
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Systematic Reed-Solomon erasure code over GF(2^8)
+//!
+//! The parity shards are calculated using a Cauchy matrix, so any `data_shards` of the
+//! `data_shards + parity_shards` shards of a stripe are enough to reconstruct the others.
+
+/// Reducing polynomial of the field (x^8 + x^4 + x^3 + x^2 + 1)
+const POLYNOMIAL: u16 = 0x11d;
+
+/// Exponent and logarithm tables of the field, using 2 as the generator
+///
+/// The exponent table is repeated, so the sum of two logarithms can be looked up directly.
+#[allow(clippy::indexing_slicing, clippy::cast_possible_truncation)]
+const TABLES: ([u8; 512], [u8; 256]) = {
+    let mut exp = [0; 512];
+    let mut log = [0; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        log[x as usize] = i as u8;
+
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= POLYNOMIAL;
+        }
+
+        i += 1;
+    }
+
+    (exp, log)
+};
+
+#[allow(clippy::indexing_slicing)]
+fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+
+    let (exp, log) = &TABLES;
+    exp[usize::from(log[usize::from(a)]) + usize::from(log[usize::from(b)])]
+}
+
+/// Returns the multiplicative inverse of `a`, which must not be 0.
+#[allow(clippy::indexing_slicing)]
+fn inv(a: u8) -> u8 {
+    let (exp, log) = &TABLES;
+    exp[255 - usize::from(log[usize::from(a)])]
+}
+
+/// Adds `input` multiplied by `factor` to `out`.
+fn mul_add(out: &mut [u8], input: &[u8], factor: u8) {
+    if factor == 0 {
+        return;
+    }
+
+    let mut table = [0; 256];
+    for (x, product) in (0..=255).zip(&mut table) {
+        *product = mul(factor, x);
+    }
+
+    for (out, input) in out.iter_mut().zip(input) {
+        #[allow(clippy::indexing_slicing)]
+        {
+            *out ^= table[usize::from(*input)];
+        }
+    }
+}
+
+/// Inverts a square matrix using Gauss-Jordan elimination.
+///
+/// Returns `None` if the matrix is singular.
+#[allow(clippy::indexing_slicing, clippy::needless_range_loop)]
+fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n = matrix.len();
+
+    let mut inverse = (0..n)
+        .map(|row| (0..n).map(|col| u8::from(row == col)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // NOTE: All rows and columns are in `0..n`
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| matrix[row][col] != 0)?;
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let factor = inv(matrix[col][col]);
+        for x in 0..n {
+            matrix[col][x] = mul(matrix[col][x], factor);
+            inverse[col][x] = mul(inverse[col][x], factor);
+        }
+
+        for row in 0..n {
+            let factor = matrix[row][col];
+
+            if row == col || factor == 0 {
+                continue;
+            }
+
+            for x in 0..n {
+                matrix[row][x] ^= mul(matrix[col][x], factor);
+                inverse[row][x] ^= mul(inverse[col][x], factor);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// Reed-Solomon code with a fixed number of data and parity shards per stripe
+pub struct Codec {
+    data_shards: usize,
+    parity_shards: usize,
+}
+
+impl Codec {
+    /// Creates a new codec, the total number of shards needs to be at most 256.
+    pub fn new(data_shards: usize, parity_shards: usize) -> Self {
+        debug_assert!(data_shards + parity_shards <= 256);
+
+        Self {
+            data_shards,
+            parity_shards,
+        }
+    }
+
+    /// Returns the coefficient of data shard `data` in parity shard `parity`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn coefficient(&self, parity: usize, data: usize) -> u8 {
+        // NOTE: The parity and data shards use distinct field elements, so their sum is never 0
+        inv(((self.data_shards + parity) as u8) ^ (data as u8))
+    }
+
+    /// Returns the row of the encoding matrix that calculates shard `shard` from the data shards.
+    fn row(&self, shard: usize) -> Vec<u8> {
+        (0..self.data_shards)
+            .map(|data| {
+                if shard < self.data_shards {
+                    u8::from(shard == data)
+                } else {
+                    self.coefficient(shard - self.data_shards, data)
+                }
+            })
+            .collect()
+    }
+
+    /// Calculates the parity shards of a stripe.
+    pub fn encode(&self, data: &[Vec<u8>], parity: &mut [Vec<u8>]) {
+        for (idx, out) in parity.iter_mut().enumerate() {
+            out.fill(0);
+
+            for (data_idx, input) in data.iter().enumerate() {
+                mul_add(out, input, self.coefficient(idx, data_idx));
+            }
+        }
+    }
+
+    /// Returns `true` if the parity shards of a stripe (data shards followed by parity shards)
+    /// match its data shards.
+    pub fn is_consistent(&self, shards: &[Vec<u8>]) -> bool {
+        let (data, parity) = shards.split_at(self.data_shards.min(shards.len()));
+        let shard_size = shards.first().map_or(0, Vec::len);
+
+        let mut recalculated = vec![vec![0; shard_size]; parity.len()];
+        self.encode(data, &mut recalculated);
+
+        recalculated == parity
+    }
+
+    /// Reconstructs the shards of a stripe (data shards followed by parity shards)
+    /// that are not marked as present, which need to have the same size as the others.
+    ///
+    /// Returns `false` if too many shards are missing.
+    pub fn reconstruct(&self, shards: &mut [Vec<u8>], present: &[bool]) -> bool {
+        let available = present
+            .iter()
+            .enumerate()
+            .filter(|(_, present)| **present)
+            .map(|(idx, _)| idx)
+            .take(self.data_shards)
+            .collect::<Vec<_>>();
+
+        if available.len() < self.data_shards {
+            return false;
+        }
+
+        let Some(decoding) = invert(available.iter().map(|&idx| self.row(idx)).collect()) else {
+            return false;
+        };
+
+        let shard_size = shards.first().map_or(0, Vec::len);
+
+        for (idx, row) in decoding.iter().enumerate() {
+            if present.get(idx).copied().unwrap_or(true) {
+                continue;
+            }
+
+            let mut out = vec![0; shard_size];
+            for (&factor, &input) in row.iter().zip(&available) {
+                if let Some(input) = shards.get(input) {
+                    mul_add(&mut out, input, factor);
+                }
+            }
+
+            if let Some(shard) = shards.get_mut(idx) {
+                *shard = out;
+            }
+        }
+
+        if present
+            .iter()
+            .skip(self.data_shards)
+            .take(self.parity_shards)
+            .all(|present| *present)
+        {
+            return true;
+        }
+
+        let (data, parity) = shards.split_at_mut(self.data_shards.min(shards.len()));
+        let mut recalculated = vec![vec![0; shard_size]; parity.len()];
+        self.encode(data, &mut recalculated);
+
+        for ((shard, recalculated), present) in parity
+            .iter_mut()
+            .zip(recalculated)
+            .zip(present.iter().skip(self.data_shards))
+        {
+            if !present {
+                *shard = recalculated;
+            }
+        }
+
+        true
+    }
+}
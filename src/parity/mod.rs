@@ -0,0 +1,396 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Parity region
+//!
+//! If enabled, the bytes of all sections (in file order, without section markers or other
+//! metadata) form the data stream, which is split into shards of a fixed size. Each stripe of
+//! `data_shards` consecutive shards is protected by `parity_shards` Reed-Solomon parity shards,
+//! so up to `parity_shards` damaged shards per stripe can be reconstructed.
+//!
+//! The parity region is located directly before the block index (or where it would be),
+//! and contains the parity shards of all stripes, followed by a checksum of each parity shard,
+//! and a footer. The digest of the checksums and footer is stored in the trailer, so the
+//! parity region is covered by the trailer checksum and the signature (if any).
+
+pub mod codec;
+
+use crate::{
+    checksum::{ChecksumType, Hasher},
+    toc::entry::TocEntry,
+    trailer::reader::ParsedTrailer,
+    Checksum, RandomAccessSource,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use codec::Codec;
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+pub const PARITY_MAGIC: &[u8] = b"PAR!";
+
+/// Size of the footer that ends the parity region
+pub const FOOTER_SIZE: usize = 4 + 1 + 1 + 8 + 8 + PARITY_MAGIC.len();
+
+/// Shard size and number of data and parity shards per stripe
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParityLayout {
+    pub shard_size: u32,
+    pub data_shards: u8,
+    pub parity_shards: u8,
+}
+
+impl ParityLayout {
+    /// Returns `true` if the layout can be used to encode stripes.
+    pub fn is_valid(self) -> bool {
+        self.shard_size > 0
+            && self.data_shards > 0
+            && self.parity_shards > 0
+            && usize::from(self.data_shards) + usize::from(self.parity_shards) <= 256
+    }
+
+    pub fn codec(self) -> Codec {
+        Codec::new(
+            usize::from(self.data_shards),
+            usize::from(self.parity_shards),
+        )
+    }
+
+    /// Returns the number of stripes needed for a data stream of `data_len` bytes.
+    pub fn stripe_count(self, data_len: u64) -> u64 {
+        data_len
+            .div_ceil(u64::from(self.shard_size))
+            .div_ceil(u64::from(self.data_shards))
+    }
+
+    /// Returns the size of a stripe's data in bytes.
+    pub fn stripe_size(self) -> u64 {
+        u64::from(self.shard_size) * u64::from(self.data_shards)
+    }
+}
+
+/// The bytes of all sections, in file order, without gaps
+///
+/// Aliases do not add bytes, because they lie within other sections.
+pub struct DataStream {
+    /// Start in the data stream, position in the file and length of each contiguous range
+    ranges: Vec<(u64, u64, u64)>,
+    len: u64,
+}
+
+impl DataStream {
+    pub fn new(entries: &[TocEntry]) -> Self {
+        let mut sections = entries
+            .iter()
+            .filter(|entry| entry.len > 0)
+            .map(|entry| (entry.pos, entry.pos.saturating_add(entry.len)))
+            .collect::<Vec<_>>();
+        sections.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = vec![];
+
+        for (start, end) in sections {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        let mut len = 0;
+        let ranges = merged
+            .into_iter()
+            .map(|(start, end)| {
+                let range = (len, start, end - start);
+                len += end - start;
+                range
+            })
+            .collect();
+
+        Self { ranges, len }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the offset in the data stream of the given position in the file, if it belongs to a section.
+    pub fn offset_of(&self, pos: u64) -> Option<u64> {
+        let idx = self
+            .ranges
+            .partition_point(|(_, start, _)| *start <= pos)
+            .checked_sub(1)?;
+
+        let (offset, start, len) = self.ranges.get(idx)?;
+        (pos - start < *len).then(|| offset + (pos - start))
+    }
+
+    /// Returns the file positions and buffer ranges of the stream bytes `offset..offset + len`,
+    /// ignoring bytes after the end of the stream.
+    fn segments(&self, offset: u64, len: usize) -> Vec<(u64, Range<usize>)> {
+        let end = offset.saturating_add(len as u64).min(self.len);
+
+        let first = self
+            .ranges
+            .partition_point(|(range_offset, _, _)| *range_offset <= offset)
+            .saturating_sub(1);
+
+        self.ranges
+            .iter()
+            .skip(first)
+            .take_while(|(range_offset, _, _)| *range_offset < end)
+            .filter_map(|(range_offset, pos, range_len)| {
+                let start = offset.max(*range_offset);
+                let stop = end.min(range_offset + range_len);
+
+                // NOTE: Buffer offsets are bounded by `len`
+                #[allow(clippy::cast_possible_truncation)]
+                (start < stop).then(|| {
+                    (
+                        pos + (start - range_offset),
+                        (start - offset) as usize..(stop - offset) as usize,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the stream bytes starting at `offset` into `buf`, filling bytes after the end of the stream with zeros.
+    pub fn read_at<S: RandomAccessSource + ?Sized>(
+        &self,
+        source: &S,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> std::io::Result<()> {
+        buf.fill(0);
+
+        for (pos, range) in self.segments(offset, buf.len()) {
+            if let Some(buf) = buf.get_mut(range) {
+                source.read_at(pos, buf)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `buf` to the stream bytes starting at `offset`, ignoring bytes after the end of the stream.
+    pub fn write_at<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        offset: u64,
+        buf: &[u8],
+    ) -> std::io::Result<()> {
+        for (pos, range) in self.segments(offset, buf.len()) {
+            if let Some(buf) = buf.get(range) {
+                writer.seek(SeekFrom::Start(pos))?;
+                writer.write_all(buf)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the parity region of the data stream read from `source`,
+/// returning its digest, which needs to be stored in the trailer.
+pub fn write_into<S: RandomAccessSource + ?Sized>(
+    mut writer: impl Write,
+    source: &S,
+    stream: &DataStream,
+    layout: ParityLayout,
+    checksum_type: ChecksumType,
+) -> std::io::Result<Checksum> {
+    use byteorder::LE;
+
+    log::trace!("Writing parity region");
+
+    let codec = layout.codec();
+    let shard_size = layout.shard_size as usize;
+
+    let mut data = vec![vec![0; shard_size]; usize::from(layout.data_shards)];
+    let mut parity = vec![vec![0; shard_size]; usize::from(layout.parity_shards)];
+    let mut checksums = vec![];
+
+    for stripe in 0..layout.stripe_count(stream.len()) {
+        for (idx, shard) in data.iter_mut().enumerate() {
+            let offset = stripe * layout.stripe_size() + idx as u64 * u64::from(layout.shard_size);
+            stream.read_at(source, offset, shard)?;
+        }
+
+        codec.encode(&data, &mut parity);
+
+        for shard in &parity {
+            let mut hasher = Hasher::new(checksum_type);
+            hasher.update(shard);
+            checksums.push(hasher.checksum());
+
+            writer.write_all(shard)?;
+        }
+    }
+
+    let mut buf = vec![];
+
+    for checksum in &checksums {
+        buf.write_all(checksum.as_bytes())?;
+    }
+
+    buf.write_u32::<LE>(layout.shard_size)?;
+    buf.write_u8(layout.data_shards)?;
+    buf.write_u8(layout.parity_shards)?;
+    buf.write_u64::<LE>(stream.len())?;
+
+    let checksum = xxhash_rust::xxh3::xxh3_64(&buf);
+    buf.write_u64::<LE>(checksum)?;
+    buf.write_all(PARITY_MAGIC)?;
+
+    writer.write_all(&buf)?;
+
+    let mut hasher = Hasher::new(checksum_type);
+    hasher.update(&buf);
+
+    Ok(hasher.checksum())
+}
+
+/// Parity region read from an archive
+pub struct ParityRegion {
+    /// Position of the first parity shard
+    pub pos: u64,
+
+    pub layout: ParityLayout,
+
+    /// Length of the data stream the parity was calculated over
+    pub data_len: u64,
+
+    /// Checksums of the parity shards
+    pub checksums: Vec<Checksum>,
+}
+
+impl ParityRegion {
+    /// Returns the position of parity shard `idx`.
+    pub fn shard_pos(&self, idx: u64) -> u64 {
+        self.pos + idx * u64::from(self.layout.shard_size)
+    }
+}
+
+/// Returns the position where the parity region is expected to end, which is the start of the block index
+/// (given its position), or the start of the mirrored table of contents (or the table of contents).
+pub fn end<R: Read + Seek>(
+    reader: &mut R,
+    trailer: &ParsedTrailer,
+    block_index_pos: Option<u64>,
+) -> crate::Result<Option<u64>> {
+    match block_index_pos {
+        Some(pos) => Ok(Some(pos)),
+        None => crate::block_index::end(reader, trailer),
+    }
+}
+
+/// Reads the parity region ending at `end`, without reading the parity shards,
+/// and checks it against the digest stored in the trailer.
+pub fn read<R: Read + Seek>(
+    reader: &mut R,
+    end: u64,
+    expected: Option<Checksum>,
+) -> crate::Result<ParityRegion> {
+    use byteorder::LE;
+
+    log::trace!("Reading parity region");
+
+    let invalid = |offset| crate::Error::InvalidParity { path: None, offset };
+
+    let Some(expected) = expected else {
+        log::error!("Trailer does not contain a parity region digest");
+        return Err(invalid(end));
+    };
+
+    let checksum_type = expected.checksum_type();
+
+    let Some(footer_pos) = end.checked_sub(FOOTER_SIZE as u64) else {
+        log::error!("Invalid parity region position");
+        return Err(invalid(end));
+    };
+
+    reader.seek(SeekFrom::Start(footer_pos))?;
+
+    let layout = ParityLayout {
+        shard_size: reader.read_u32::<LE>()?,
+        data_shards: reader.read_u8()?,
+        parity_shards: reader.read_u8()?,
+    };
+    let data_len = reader.read_u64::<LE>()?;
+    let checksum = reader.read_u64::<LE>()?;
+
+    {
+        let mut buf = [0u8; PARITY_MAGIC.len()];
+        reader.read_exact(&mut buf)?;
+
+        if buf != PARITY_MAGIC {
+            log::error!("Invalid parity region footer");
+            return Err(invalid(footer_pos));
+        }
+    }
+
+    if !layout.is_valid() {
+        log::error!("Invalid parity layout {layout:?}");
+        return Err(invalid(footer_pos));
+    }
+
+    // NOTE: The layout and data length are untrusted, so check the parity region fits into the file
+    let digest_len = checksum_type.digest_len() as u64;
+    let shard_count = layout
+        .stripe_count(data_len)
+        .saturating_mul(u64::from(layout.parity_shards));
+
+    let Some((pos, checksums_pos)) = shard_count
+        .checked_mul(digest_len)
+        .and_then(|checksums_len| footer_pos.checked_sub(checksums_len))
+        .and_then(|checksums_pos| {
+            let shards_len = shard_count.checked_mul(u64::from(layout.shard_size))?;
+            Some((checksums_pos.checked_sub(shards_len)?, checksums_pos))
+        })
+    else {
+        log::error!("Parity region exceeds file");
+        return Err(invalid(footer_pos));
+    };
+
+    reader.seek(SeekFrom::Start(checksums_pos))?;
+
+    // NOTE: The checksums are located before the footer, so their length is bounded by the file size
+    #[allow(clippy::cast_possible_truncation)]
+    let mut bytes = vec![0; (end - checksums_pos) as usize];
+    reader.read_exact(&mut bytes)?;
+
+    let covered = bytes
+        .get(..bytes.len() - 8 - PARITY_MAGIC.len())
+        .unwrap_or_default();
+
+    if xxhash_rust::xxh3::xxh3_64(covered) != checksum {
+        log::error!("Parity region checksum mismatch");
+        return Err(invalid(checksums_pos));
+    }
+
+    {
+        let mut hasher = Hasher::new(checksum_type);
+        hasher.update(&bytes);
+
+        if hasher.checksum() != expected {
+            log::error!("Parity region digest mismatch");
+            return Err(invalid(checksums_pos));
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let checksums = bytes
+        .chunks_exact(digest_len as usize)
+        .take(shard_count as usize)
+        .map(|digest| Checksum::from_bytes(checksum_type, digest))
+        .collect();
+
+    Ok(ParityRegion {
+        pos,
+        layout,
+        data_len,
+        checksums,
+    })
+}
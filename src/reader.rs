@@ -3,14 +3,44 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
+    block_index::BlockIndex,
+    checksum::Hasher,
+    checksum_reader::ChecksummedReader,
+    tail_reader::TailReader,
     toc::{reader::TocReader, Toc},
-    trailer::reader::TrailerReader,
+    trailer::reader::{ParsedTrailer, TrailerReader},
+    Checksum, ChecksumType, FinishedArchive, InclusionProof, RandomAccessSource, ReaderOptions,
+    RepairReport, SourceReader, TocEntry, ValidationReport, VerifiedReader, Writer,
 };
-use std::io::{BufReader, Read, Seek};
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+/// Size of the chunks that are read when verifying sections in parallel
+const VERIFY_CHUNK_SIZE: usize = 1_024 * 1_024;
+
+#[cfg(feature = "signing")]
+use crate::VerifyingKey;
 
 /// Archive reader
 pub struct Reader {
     toc: Toc,
+    checksum_type: ChecksumType,
+    section_markers: bool,
+    block_index: BlockIndex,
+    merkle_root: Option<Checksum>,
+
+    /// Position where the parity region ends (if the archive has one)
+    parity_end: Option<u64>,
+
+    /// Digest of the parity region stored in the trailer
+    parity_digest: Option<Checksum>,
 }
 
 impl Reader {
@@ -19,12 +49,162 @@ impl Reader {
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub fn new(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
-        let file = std::fs::File::open(path)?;
-        let mut file = BufReader::with_capacity(4_096, file);
-        let trailer = TrailerReader::from_reader(&mut file)?;
-        let toc = TocReader::from_reader(&mut file, trailer.toc_pos, trailer.toc_checksum)?;
-        Ok(Self { toc })
+    pub fn new(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::with_options(path, &ReaderOptions::default())
+    }
+
+    /// Creates a new [`Reader`] from a file path, rejecting archives
+    /// that exceed the limits of the given [`ReaderOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive exceeds a limit.
+    pub fn with_options(path: impl AsRef<Path>, options: &ReaderOptions) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut file = std::fs::File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+        Self::from_reader_with_options(&mut file, options).map_err(|e| e.with_path(path))
+    }
+
+    /// Creates a new [`Reader`] from a file path, requiring the archive to be signed
+    /// by one of the given public keys.
+    ///
+    /// Use [`Reader::verify`] to also check the section contents against
+    /// the (now authenticated) section checksums.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the archive is not signed,
+    /// does not use a cryptographic [`ChecksumType`], or the signature is invalid.
+    #[cfg(feature = "signing")]
+    pub fn new_verified(
+        path: impl AsRef<Path>,
+        public_keys: &[VerifyingKey],
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+        Self::read_verified(file, public_keys).map_err(|e| e.with_path(path))
+    }
+
+    #[cfg(feature = "signing")]
+    fn read_verified(file: std::fs::File, public_keys: &[VerifyingKey]) -> crate::Result<Self> {
+        use crate::signature::SIGNATURE_LEN;
+        use std::io::SeekFrom;
+
+        let options = ReaderOptions::default();
+        let file = &mut TailReader::new(file, options.tail_size)?;
+
+        let trailer = TrailerReader::from_reader(file)?;
+
+        if !trailer.is_signed() {
+            log::error!("Archive is not signed");
+            return Err(crate::Error::MissingSignature { path: None });
+        }
+
+        // NOTE: The signature only covers the section checksums, so it does not
+        // protect the section contents if checksums can be forged
+        let checksum_type = trailer.toc_checksum.checksum_type();
+
+        if !checksum_type.is_cryptographic() {
+            log::error!(
+                "Archive is signed, but uses non-cryptographic checksum type {checksum_type:?}"
+            );
+            return Err(crate::Error::InsecureChecksumType {
+                path: None,
+                checksum_type,
+            });
+        }
+
+        // NOTE: The trailer size is bounded by the version 3 trailer size
+        #[allow(clippy::cast_possible_truncation)]
+        let mut trailer_bytes = vec![0; trailer.size() as usize];
+        file.seek(SeekFrom::Start(trailer.pos))?;
+        file.read_exact(&mut trailer_bytes)?;
+
+        let toc_bytes = TocReader::read_bytes(file, &trailer, &options)?;
+
+        let mut signature = [0; SIGNATURE_LEN];
+        file.read_exact(&mut signature)?;
+
+        crate::signature::verify(public_keys, &toc_bytes, &trailer_bytes, &signature)?;
+
+        let toc = TocReader::parse(&toc_bytes, &trailer, &options)?;
+        let (block_index_pos, block_index) = Self::read_block_index(file, &trailer, &options)?;
+        let merkle_root = Self::check_merkle_root(&toc, &trailer)?;
+        Ok(Self {
+            toc,
+            checksum_type: trailer.toc_checksum.checksum_type(),
+            section_markers: trailer.has_section_markers(),
+            block_index,
+            merkle_root,
+            parity_end: Self::parity_end(file, &trailer, block_index_pos)?,
+            parity_digest: trailer.parity_digest,
+        })
+    }
+
+    /// Recovers all complete sections of an archive by scanning for section markers,
+    /// without reading the table of contents or trailer.
+    ///
+    /// This requires the archive to be written with [`Writer::use_section_markers`].
+    /// Sections that were not closed before the writer stopped, or fail
+    /// their checksum, are skipped.
+    ///
+    /// The recovered sections can be read from the damaged file, or written
+    /// into a new archive using [`Reader::rewrite`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn salvage(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut file = std::fs::File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+        let (checksum_type, entries) =
+            crate::marker::scan(&mut file).map_err(|e| e.with_path(path))?;
+
+        log::debug!("Salvaged {} sections", entries.len());
+
+        Ok(Self {
+            toc: Toc(entries),
+            checksum_type: checksum_type.unwrap_or_default(),
+            section_markers: true,
+            block_index: BlockIndex::new(),
+            merkle_root: None,
+            parity_end: None,
+            parity_digest: None,
+        })
+    }
+
+    /// Checks the structure of the archive at `path`, beyond its checksums.
+    ///
+    /// Reports overlapping sections, sections that extend into the table of contents,
+    /// duplicate section names, unused bytes in the table of contents, and bytes that
+    /// are not referenced by the archive (including bytes after the trailer).
+    /// Section markers, the parity region, the block index and the mirrored table of contents
+    /// are taken into account.
+    ///
+    /// Section contents are not checked, use [`Reader::verify`] for that.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the trailer or table of contents
+    /// cannot be read at all.
+    pub fn validate(path: impl AsRef<Path>) -> crate::Result<ValidationReport> {
+        Self::validate_with_options(path, &ReaderOptions::default())
+    }
+
+    /// Checks the structure of the archive at `path` (see [`Reader::validate`]),
+    /// rejecting archives that exceed the limits of the given [`ReaderOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the trailer or table of contents
+    /// cannot be read at all, or the archive exceeds a limit.
+    pub fn validate_with_options(
+        path: impl AsRef<Path>,
+        options: &ReaderOptions,
+    ) -> crate::Result<ValidationReport> {
+        let path = path.as_ref();
+        let mut file = std::fs::File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+        crate::validate::validate(&mut file, options).map_err(|e| e.with_path(path))
     }
 
     /// Creates a new [`Reader`] from a reader.
@@ -32,10 +212,148 @@ impl Reader {
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub fn from_reader<R: Read + Seek>(mut reader: &mut R) -> crate::Result<Self> {
-        let trailer = TrailerReader::from_reader(&mut reader)?;
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
-        Ok(Self { toc })
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> crate::Result<Self> {
+        Self::from_reader_with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Creates a new [`Reader`] from a reader, rejecting archives
+    /// that exceed the limits of the given [`ReaderOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive exceeds a limit.
+    pub fn from_reader_with_options<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReaderOptions,
+    ) -> crate::Result<Self> {
+        let mut reader = TailReader::new(reader, options.tail_size)?;
+        let trailer = Self::read_trailer(&mut reader, options)?;
+        let toc = Self::read_toc(&mut reader, &trailer, options)?;
+        let (block_index_pos, block_index) =
+            Self::read_block_index(&mut reader, &trailer, options)?;
+        let merkle_root = Self::check_merkle_root(&toc, &trailer)?;
+        Ok(Self {
+            toc,
+            checksum_type: trailer.toc_checksum.checksum_type(),
+            section_markers: trailer.has_section_markers(),
+            block_index,
+            merkle_root,
+            parity_end: Self::parity_end(&mut reader, &trailer, block_index_pos)?,
+            parity_digest: trailer.parity_digest,
+        })
+    }
+
+    /// Creates a new [`Reader`] from a [`RandomAccessSource`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn from_source<S: RandomAccessSource>(source: S) -> crate::Result<Self> {
+        Self::from_source_with_options(source, &ReaderOptions::default())
+    }
+
+    /// Creates a new [`Reader`] from a [`RandomAccessSource`], rejecting archives
+    /// that exceed the limits of the given [`ReaderOptions`].
+    ///
+    /// The trailer and table of contents are usually read with a single
+    /// positional read (see [`ReaderOptions::tail_size`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive exceeds a limit.
+    pub fn from_source_with_options<S: RandomAccessSource>(
+        source: S,
+        options: &ReaderOptions,
+    ) -> crate::Result<Self> {
+        Self::from_reader_with_options(&mut SourceReader::new(source)?, options)
+    }
+
+    /// Reads the trailer, falling back to the mirrored trailer if it is corrupted.
+    fn read_trailer<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReaderOptions,
+    ) -> crate::Result<ParsedTrailer> {
+        match TrailerReader::from_reader(reader) {
+            Ok(trailer) => Ok(trailer),
+            Err(crate::Error::Io(e)) => Err(crate::Error::Io(e)),
+            Err(e) => {
+                log::warn!("Trailer is corrupted ({e:?}), searching for mirror");
+                TrailerReader::find_mirror(reader, options.max_toc_size)?.ok_or(e)
+            }
+        }
+    }
+
+    /// Reads the table of contents, falling back to the mirrored copy if it is corrupted.
+    fn read_toc<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> crate::Result<Toc> {
+        match TocReader::from_reader(reader, trailer, options) {
+            Ok(toc) => Ok(toc),
+            Err(e) if trailer.has_toc_mirror() => {
+                log::warn!("Table of contents is corrupted ({e:?}), falling back to mirror");
+
+                TrailerReader::read_mirror(reader, trailer)
+                    .and_then(|mirror| TocReader::from_reader(reader, &mirror, options))
+                    .map_err(|mirror_error| {
+                        log::error!("Mirrored table of contents is corrupted: {mirror_error:?}");
+                        e
+                    })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads the block index, if the archive has one, returning its position and the block checksums.
+    fn read_block_index<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> crate::Result<(Option<u64>, BlockIndex)> {
+        if !trailer.has_block_checksums() {
+            return Ok((None, BlockIndex::new()));
+        }
+
+        let (pos, index) = crate::block_index::read(reader, trailer, options)?;
+
+        Ok((Some(pos), index))
+    }
+
+    /// Returns the position where the parity region ends, if the archive has one.
+    ///
+    /// The parity region itself is only read when it is needed (see [`Reader::repair`]).
+    fn parity_end<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+        block_index_pos: Option<u64>,
+    ) -> crate::Result<Option<u64>> {
+        if !trailer.has_parity() {
+            return Ok(None);
+        }
+
+        crate::parity::end(reader, trailer, block_index_pos)
+    }
+
+    /// Checks the Merkle root in the trailer, if any, against the table of contents.
+    fn check_merkle_root(toc: &Toc, trailer: &ParsedTrailer) -> crate::Result<Option<Checksum>> {
+        let Some(expected) = trailer.merkle_root else {
+            return Ok(None);
+        };
+
+        let got = crate::merkle::leaves(toc)
+            .map(|leaves| crate::merkle::root(expected.checksum_type(), &leaves));
+
+        if got != Some(expected) {
+            log::error!("Merkle root mismatch: expected {expected}, got {got:?}");
+            return Err(crate::Error::MerkleRootMismatch {
+                path: None,
+                got: got.map(Box::new),
+                expected: Box::new(expected),
+            });
+        }
+
+        Ok(Some(expected))
     }
 
     /// Lists the table of contents.
@@ -43,4 +361,374 @@ impl Reader {
     pub fn toc(&self) -> &Toc {
         &self.toc
     }
+
+    /// Returns the checksum algorithm the archive was written with.
+    #[must_use]
+    pub fn checksum_type(&self) -> ChecksumType {
+        self.checksum_type
+    }
+
+    /// Returns the Merkle root over the sections, if the archive has one
+    /// (see [`Writer::use_merkle_root`]).
+    ///
+    /// The Merkle root was checked against the table of contents when the archive was opened.
+    #[must_use]
+    pub fn merkle_root(&self) -> Option<Checksum> {
+        self.merkle_root
+    }
+
+    /// Creates a proof that the first section named `name` is part of the archive,
+    /// which can be checked against the archive's Merkle root using [`verify_proof`](crate::verify_proof).
+    ///
+    /// Returns `None` if the archive has no Merkle root, or there is no such section.
+    ///
+    /// ```
+    /// use sfa::{Reader, Writer};
+    /// use std::io::Write;
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("hello.sfa");
+    ///
+    /// let mut writer = Writer::new_at_path(&path)?.use_merkle_root(true);
+    /// writer.section("Section 1")?.write_all(b"Hello world!\n")?;
+    /// writer.section("Section 2")?.write_all(b"Hello again!\n")?;
+    /// let root = writer.finish()?.merkle_root().unwrap();
+    ///
+    /// let reader = Reader::new(&path)?;
+    /// let proof = reader.inclusion_proof(b"Section 2").unwrap();
+    ///
+    /// // The client only receives the section, its checksum and the proof
+    /// let digest = proof.entry().checksum().unwrap();
+    /// assert!(sfa::verify_proof(root, proof.entry(), digest, &proof));
+    /// #
+    /// # Ok::<(), sfa::Error>(())
+    /// ```
+    #[must_use]
+    pub fn inclusion_proof(&self, name: &[u8]) -> Option<InclusionProof> {
+        self.merkle_root?;
+
+        let index = self.toc.iter().position(|entry| entry.name() == name)?;
+        InclusionProof::new(&self.toc, index)
+    }
+
+    /// Returns a reader over the section's bytes in the given source, which verifies
+    /// every block it reads against the section's block checksums.
+    ///
+    /// Returns `None` if no block checksums are stored for the section
+    /// (see [`Writer::use_block_checksums`]).
+    ///
+    /// ```
+    /// use sfa::{Reader, Writer};
+    /// use std::io::{Read, Seek, SeekFrom, Write};
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("hello.sfa");
+    ///
+    /// let mut writer = Writer::new_at_path(&path)?.use_block_checksums(4);
+    /// writer.section("Section 1")?.write_all(b"Hello world!\n")?;
+    /// writer.finish()?;
+    ///
+    /// let reader = Reader::new(&path)?;
+    /// let mut section = reader
+    ///     .verified_reader(&reader.toc()[0], std::fs::File::open(&path)?)
+    ///     .expect("section should have block checksums");
+    ///
+    /// // Only the blocks containing "world" are read and verified
+    /// let mut buf = [0; 5];
+    /// section.seek(SeekFrom::Start(6))?;
+    /// section.read_exact(&mut buf)?;
+    /// assert_eq!(b"world", &buf);
+    /// #
+    /// # Ok::<(), sfa::Error>(())
+    /// ```
+    pub fn verified_reader<S: RandomAccessSource>(
+        &self,
+        entry: &TocEntry,
+        source: S,
+    ) -> Option<VerifiedReader<S>> {
+        let checksums = self.block_index.get(&(entry.pos(), entry.len()))?;
+
+        Some(VerifiedReader::new(
+            source,
+            entry,
+            self.checksum_type,
+            checksums.clone(),
+        ))
+    }
+
+    /// Writes all sections into a new archive at `dest`, copying them from the archive at `path`
+    /// using [`Writer::copy_section_from`].
+    ///
+    /// The new archive uses the same checksum type, section marker, block checksum, Merkle root
+    /// and parity settings.
+    ///
+    /// Returns a summary of the new archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the parity region cannot be read.
+    /// Corrupted sections keep their stored checksums, so they are still detected
+    /// when reading the new archive.
+    pub fn rewrite(
+        &self,
+        path: impl AsRef<Path>,
+        dest: impl Into<PathBuf>,
+    ) -> crate::Result<FinishedArchive> {
+        let path = path.as_ref();
+
+        let parity = match self.parity_end {
+            Some(end) => {
+                let mut file = File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+                let parity = crate::parity::read(&mut file, end, self.parity_digest)
+                    .map_err(|e| e.with_path(path))?;
+                Some(parity.layout)
+            }
+            None => None,
+        };
+
+        let mut writer = Writer::new_at_path(dest)?
+            .use_checksum_type(self.checksum_type)
+            .use_section_markers(self.section_markers)
+            .use_merkle_root(self.merkle_root.is_some())
+            .use_block_checksums(
+                self.block_index
+                    .values()
+                    .next()
+                    .map_or(0, |checksums| checksums.block_size),
+            );
+
+        if let Some(layout) = parity {
+            writer = writer.use_parity(layout.shard_size, layout.data_shards, layout.parity_shards);
+        }
+
+        for entry in self.toc.iter() {
+            writer.copy_section_from(path, entry)?;
+        }
+
+        writer.finish()
+    }
+
+    /// Finds damaged sections in the archive at `path`, and repairs them in place
+    /// using the archive's parity region (see [`Writer::use_parity`]).
+    ///
+    /// Damaged bytes are located using the block checksums of a section if it has any
+    /// (see [`Writer::use_block_checksums`]), otherwise using the section checksum and the parity:
+    /// without block checksums, up to `parity_shards - 1` damaged shards per stripe can be
+    /// located, so block checksums make repairs more robust.
+    /// Damaged parity shards are found and repaired as well. The file is synced after repairing.
+    ///
+    /// The report lists all damaged regions that were found, and whether they were repaired;
+    /// a region can only be repaired if not too many other bytes near it are damaged as well.
+    /// If the archive has no parity region, damaged regions are reported, but not repaired.
+    ///
+    /// The table of contents and other archive metadata are not repaired, and need to be intact
+    /// (the [`Reader`] may use the mirrored table of contents, though). Sections without
+    /// a checksum (format version 1) cannot be checked, so they need to be intact as well.
+    ///
+    /// ```
+    /// use sfa::{Reader, Writer};
+    /// use std::io::Write;
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("hello.sfa");
+    ///
+    /// let mut writer = Writer::new_at_path(&path)?.use_parity(16, 4, 1);
+    /// writer.section("Section 1")?.write_all(b"Hello world!\n")?;
+    /// writer.finish()?;
+    ///
+    /// // Flip a bit of the section
+    /// let mut bytes = std::fs::read(&path)?;
+    /// bytes[6] ^= 1;
+    /// std::fs::write(&path, &bytes)?;
+    ///
+    /// let reader = Reader::new(&path)?;
+    /// let report = reader.repair(&path)?;
+    /// assert_eq!(1, report.regions().len());
+    /// assert!(report.is_repaired());
+    ///
+    /// reader.verify(&path)?;
+    /// #
+    /// # Ok::<(), sfa::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the parity region is corrupted
+    /// or does not match the table of contents.
+    pub fn repair(&self, path: impl AsRef<Path>) -> crate::Result<RepairReport> {
+        let path = path.as_ref();
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| crate::Error::io_at(e, path))?;
+
+        crate::repair::repair(
+            &mut file,
+            &self.toc,
+            &self.block_index,
+            self.parity_end,
+            self.parity_digest,
+        )
+        .map_err(|e| e.with_path(path))
+    }
+
+    /// Verifies the contents of all sections against their checksums.
+    ///
+    /// Sections without a checksum (format version 1) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or a section is corrupted.
+    pub fn verify(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let path = path.as_ref();
+
+        for entry in self.toc.iter() {
+            let Some(expected) = entry.checksum() else {
+                continue;
+            };
+
+            let buf_reader = entry
+                .buf_reader(path)
+                .map_err(|e| crate::Error::io_at(e, path))?;
+            let mut reader = ChecksummedReader::new(buf_reader, self.checksum_type);
+            std::io::copy(&mut reader, &mut std::io::sink())
+                .map_err(|e| crate::Error::io_at(e, path))?;
+            reader
+                .checksum()
+                .check(expected, Some(entry.name()), entry.pos())
+                .map_err(|e| e.with_path(path))?;
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the contents of all sections against their checksums, using `threads` threads.
+    ///
+    /// Unlike [`Reader::verify`], all sections are checked, even if some of them fail.
+    /// If `threads` is `0`, the available parallelism is used.
+    /// On platforms without positional file reads (other than Unix and Windows),
+    /// a single thread is used.
+    ///
+    /// `progress` is called with the number of bytes verified so far, and the total number
+    /// of bytes to verify, from the verifying threads.
+    ///
+    /// Sections without a checksum (format version 1) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the archive cannot be opened.
+    /// If any section fails (including reading it), returns [`Error::CorruptedSections`](crate::Error::CorruptedSections)
+    /// containing all failures.
+    pub fn verify_parallel(
+        &self,
+        path: impl AsRef<Path>,
+        threads: usize,
+        progress: impl Fn(u64, u64) + Sync,
+    ) -> crate::Result<()> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+
+        let mut entries = self
+            .toc
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.checksum().is_some())
+            .collect::<Vec<_>>();
+
+        // NOTE: Verifying the largest sections first keeps all threads busy until the end
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.len()));
+
+        // NOTE: Without positional reads, reading moves the cursor of the file,
+        // so it cannot be shared between threads
+        let threads = if cfg!(not(any(unix, windows))) {
+            1
+        } else if threads == 0 {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        } else {
+            threads
+        };
+
+        log::debug!(
+            "Verifying {} sections using {threads} threads",
+            entries.len(),
+        );
+
+        let total = entries.iter().map(|(_, entry)| entry.len()).sum();
+        let verified = AtomicU64::new(0);
+        let next = AtomicUsize::new(0);
+        let failures = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..threads.min(entries.len()) {
+                scope.spawn(|| {
+                    let mut buf = vec![0; VERIFY_CHUNK_SIZE];
+
+                    while let Some((idx, entry)) = entries.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let result = self.verify_section(&file, entry, &mut buf, |n| {
+                            progress(verified.fetch_add(n, Ordering::Relaxed) + n, total);
+                        });
+
+                        if let Err(e) = result {
+                            failures
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .push((*idx, e));
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut failures = failures
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        failures.sort_by_key(|(idx, _)| *idx);
+
+        log::error!("{} sections failed verification", failures.len());
+
+        Err(crate::Error::CorruptedSections {
+            path: Some(path.to_path_buf()),
+            errors: failures.into_iter().map(|(_, e)| e).collect(),
+        })
+    }
+
+    /// Verifies the contents of a section using positional reads,
+    /// calling `on_progress` with the number of bytes read after each chunk.
+    fn verify_section(
+        &self,
+        file: &File,
+        entry: &TocEntry,
+        buf: &mut [u8],
+        on_progress: impl Fn(u64),
+    ) -> crate::Result<()> {
+        let Some(expected) = entry.checksum() else {
+            return Ok(());
+        };
+
+        let mut hasher = Hasher::new(self.checksum_type);
+        let mut offset = 0;
+
+        while offset < entry.len() {
+            let n = usize::try_from(entry.len() - offset)
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            let chunk = &mut buf[..n];
+
+            file.read_at(entry.pos() + offset, chunk)?;
+            hasher.update(chunk);
+
+            offset += n as u64;
+            on_progress(n as u64);
+        }
+
+        hasher
+            .checksum()
+            .check(expected, Some(entry.name()), entry.pos())
+    }
 }
@@ -0,0 +1,101 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Default number of bytes read speculatively from the end of the archive
+const DEFAULT_TAIL_SIZE: u64 = 64 * 1_024;
+
+/// Options for opening an archive
+///
+/// Archives that exceed any of the limits are rejected before the
+/// corresponding memory is allocated.
+///
+/// By default, no limits are applied (other than those implied by the file size),
+/// so tighten them when reading untrusted archives.
+///
+/// When opening an archive, the last [`ReaderOptions::tail_size`] bytes are read at once,
+/// so the trailer and table of contents can usually be parsed without any further reads.
+///
+/// ```
+/// use sfa::{Reader, ReaderOptions};
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+/// # sfa::Writer::new_at_path(&path)?.finish()?;
+///
+/// let options = ReaderOptions::default()
+///     .max_toc_size(1_000_000)
+///     .max_entry_count(1_000)
+///     .max_name_len(256)
+///     .max_block_index_size(1_000_000)
+///     .tail_size(16_000);
+///
+/// let reader = Reader::with_options(&path, &options)?;
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+#[allow(clippy::struct_field_names)]
+pub struct ReaderOptions {
+    pub(crate) max_toc_size: u64,
+    pub(crate) max_entry_count: u32,
+    pub(crate) max_name_len: u16,
+    pub(crate) max_block_index_size: u64,
+    pub(crate) tail_size: u64,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self {
+            max_toc_size: u64::MAX,
+            max_entry_count: u32::MAX,
+            max_name_len: u16::MAX,
+            max_block_index_size: u64::MAX,
+            tail_size: DEFAULT_TAIL_SIZE,
+        }
+    }
+}
+
+impl ReaderOptions {
+    /// Sets the maximum size of the table of contents in bytes.
+    #[must_use]
+    pub fn max_toc_size(mut self, bytes: u64) -> Self {
+        self.max_toc_size = bytes;
+        self
+    }
+
+    /// Sets the maximum number of table of contents entries (sections).
+    #[must_use]
+    pub fn max_entry_count(mut self, count: u32) -> Self {
+        self.max_entry_count = count;
+        self
+    }
+
+    /// Sets the maximum length of a section name in bytes.
+    #[must_use]
+    pub fn max_name_len(mut self, len: u16) -> Self {
+        self.max_name_len = len;
+        self
+    }
+
+    /// Sets the maximum size of the block index in bytes
+    /// (see [`Writer::use_block_checksums`](crate::Writer::use_block_checksums)).
+    #[must_use]
+    pub fn max_block_index_size(mut self, bytes: u64) -> Self {
+        self.max_block_index_size = bytes;
+        self
+    }
+
+    /// Sets the number of bytes read speculatively from the end of the archive
+    /// when opening it.
+    ///
+    /// If the trailer and table of contents do not fit, the rest of the table of contents
+    /// is read with a second read. On high-latency storage, the tail size should be
+    /// large enough to fit the table of contents of typical archives.
+    ///
+    /// Defaults to 64 KiB.
+    #[must_use]
+    pub fn tail_size(mut self, bytes: u64) -> Self {
+        self.tail_size = bytes;
+        self
+    }
+}
@@ -0,0 +1,512 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Repairing damaged sections using the parity region
+//!
+//! Damaged bytes are located using the block checksums of a section (if any), or else its
+//! section checksum, and the parity shards are checked against their own checksums.
+//! Each stripe with damaged shards is reconstructed from its intact shards, and the
+//! damaged shards are written back in place.
+//!
+//! A section checksum only tells that some bytes of the section are damaged, so if a section
+//! without block checksums spans more shards of a stripe than there are parity shards,
+//! the damaged shards are located by trial: sets of the section's shards are reconstructed
+//! (smallest first), until the stripe is consistent with its remaining parity shards.
+//! This needs a spare parity shard, so up to `parity_shards - 1` damaged shards per stripe
+//! can be located this way (and are only guaranteed to be found correctly for up to
+//! `parity_shards / 2`). Repaired sections are checked against their checksums in any case.
+
+use crate::{
+    block_index::BlockIndex,
+    checksum::Hasher,
+    parity::{codec::Codec, DataStream, ParityRegion},
+    toc::entry::SectionName,
+    Checksum, RandomAccessSource, Toc,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+};
+
+/// Size of the chunks that are hashed when checking a section
+const CHECK_CHUNK_SIZE: usize = 256 * 1_024;
+
+/// Maximum number of reconstructions tried per stripe when locating damaged shards
+const MAX_LOCATE_ATTEMPTS: usize = 4_096;
+
+/// Damaged bytes found by [`Reader::repair`](crate::Reader::repair)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DamagedRegion {
+    section: Option<SectionName>,
+    pos: u64,
+    len: u64,
+    repaired: bool,
+}
+
+impl DamagedRegion {
+    /// Returns the name of the section the bytes belong to, or `None` for a parity shard.
+    #[must_use]
+    pub fn section(&self) -> Option<&[u8]> {
+        self.section.as_deref()
+    }
+
+    /// Returns the position of the bytes.
+    #[must_use]
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns the number of bytes.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if there are no bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the bytes were reconstructed and match their checksum again.
+    #[must_use]
+    pub fn is_repaired(&self) -> bool {
+        self.repaired
+    }
+}
+
+/// Report returned by [`Reader::repair`](crate::Reader::repair)
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    regions: Vec<DamagedRegion>,
+}
+
+impl RepairReport {
+    /// Returns `true` if all damaged regions were repaired (or none were found).
+    #[must_use]
+    pub fn is_repaired(&self) -> bool {
+        self.regions.iter().all(DamagedRegion::is_repaired)
+    }
+
+    /// Lists all damaged regions that were found, and whether they were repaired.
+    #[must_use]
+    pub fn regions(&self) -> &[DamagedRegion] {
+        &self.regions
+    }
+}
+
+/// Damaged region, and the checksum its bytes should have
+struct Damage {
+    region: DamagedRegion,
+    expected: Checksum,
+
+    /// `true` if all bytes of the region need to be reconstructed, `false` if only some
+    /// of them are damaged (checked using a section checksum)
+    located: bool,
+}
+
+/// Returns `true` if the bytes `pos..pos + len` have the expected checksum.
+fn check(file: &File, pos: u64, len: u64, expected: &Checksum) -> std::io::Result<bool> {
+    let mut hasher = Hasher::new(expected.checksum_type());
+    let mut buf = vec![
+        0;
+        usize::try_from(len)
+            .unwrap_or(usize::MAX)
+            .min(CHECK_CHUNK_SIZE)
+    ];
+    let mut offset = 0;
+
+    while offset < len {
+        let n = usize::try_from(len - offset)
+            .unwrap_or(usize::MAX)
+            .min(buf.len());
+
+        #[allow(clippy::indexing_slicing)]
+        let chunk = &mut buf[..n];
+
+        file.read_at(pos + offset, chunk)?;
+        hasher.update(chunk);
+
+        offset += n as u64;
+    }
+
+    Ok(hasher.checksum() == *expected)
+}
+
+/// Checks all sections (once per position and length), using their block checksums if there are any.
+fn find_damaged_sections(
+    file: &File,
+    toc: &Toc,
+    block_index: &BlockIndex,
+) -> std::io::Result<Vec<Damage>> {
+    let mut checked = HashSet::new();
+    let mut damage = vec![];
+
+    for entry in toc.iter() {
+        if entry.len == 0 || !checked.insert((entry.pos, entry.len)) {
+            continue;
+        }
+
+        let mut damaged = |pos, len, expected, located| {
+            log::warn!(
+                "Section {:?} is damaged at {pos}..+{len}",
+                String::from_utf8_lossy(&entry.name),
+            );
+
+            damage.push(Damage {
+                region: DamagedRegion {
+                    section: Some(entry.name.clone()),
+                    pos,
+                    len,
+                    repaired: false,
+                },
+                expected,
+                located,
+            });
+        };
+
+        if let Some(checksums) = block_index.get(&(entry.pos, entry.len)) {
+            let block_size = u64::from(checksums.block_size);
+
+            for (idx, expected) in checksums.iter().enumerate() {
+                let offset = idx as u64 * block_size;
+                let pos = entry.pos + offset;
+                let len = block_size.min(entry.len - offset);
+
+                if !check(file, pos, len, &expected)? {
+                    damaged(pos, len, expected, true);
+                }
+            }
+        } else if let Some(expected) = entry.checksum {
+            if !check(file, entry.pos, entry.len, &expected)? {
+                damaged(entry.pos, entry.len, expected, false);
+            }
+        }
+    }
+
+    Ok(damage)
+}
+
+/// Checks all parity shards against their checksums.
+fn find_damaged_parity(file: &File, parity: &ParityRegion) -> std::io::Result<Vec<Damage>> {
+    let shard_size = u64::from(parity.layout.shard_size);
+    let mut damage = vec![];
+
+    for (idx, expected) in parity.checksums.iter().enumerate() {
+        let pos = parity.shard_pos(idx as u64);
+
+        if !check(file, pos, shard_size, expected)? {
+            log::warn!("Parity shard {idx} is damaged at {pos}");
+
+            damage.push(Damage {
+                region: DamagedRegion {
+                    section: None,
+                    pos,
+                    len: shard_size,
+                    repaired: false,
+                },
+                expected: *expected,
+                located: true,
+            });
+        }
+    }
+
+    Ok(damage)
+}
+
+/// Damaged shards of a stripe, with data shards before parity shards
+#[derive(Default)]
+struct DamagedShards {
+    /// Shards that are known to be damaged
+    known: BTreeSet<usize>,
+
+    /// Shards that may be damaged, because they overlap a damaged section
+    suspect: BTreeSet<usize>,
+}
+
+/// Returns the damaged shards of each stripe.
+fn damaged_shards(
+    damage: &[Damage],
+    stream: &DataStream,
+    parity: &ParityRegion,
+) -> BTreeMap<u64, DamagedShards> {
+    let shard_size = u64::from(parity.layout.shard_size);
+    let data_shards = u64::from(parity.layout.data_shards);
+    let parity_shards = u64::from(parity.layout.parity_shards);
+
+    let mut stripes = BTreeMap::<u64, DamagedShards>::new();
+
+    // NOTE: Shard indices within a stripe are at most 255
+    #[allow(clippy::cast_possible_truncation)]
+    for Damage {
+        region, located, ..
+    } in damage
+    {
+        if region.section.is_some() {
+            let Some(start) = stream.offset_of(region.pos) else {
+                continue;
+            };
+            let last = start + region.len.saturating_sub(1);
+
+            for shard in start / shard_size..=last / shard_size {
+                let shards = stripes.entry(shard / data_shards).or_default();
+                let idx = (shard % data_shards) as usize;
+
+                if *located {
+                    shards.known.insert(idx);
+                } else {
+                    shards.suspect.insert(idx);
+                }
+            }
+        } else {
+            let shard = (region.pos - parity.pos) / shard_size;
+
+            stripes
+                .entry(shard / parity_shards)
+                .or_default()
+                .known
+                .insert((data_shards + shard % parity_shards) as usize);
+        }
+    }
+
+    stripes
+}
+
+/// Advances `indices` to the next combination of the same size of `0..n`, in lexicographic order.
+///
+/// Returns `false` if there is none.
+fn next_combination(indices: &mut [usize], n: usize) -> bool {
+    let size = indices.len();
+
+    for idx in (0..size).rev() {
+        // NOTE: All indices are in `0..size`
+        #[allow(clippy::indexing_slicing)]
+        if indices[idx] < n - size + idx {
+            indices[idx] += 1;
+
+            for next in idx + 1..size {
+                indices[next] = indices[next - 1] + 1;
+            }
+
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Locates the damaged shards among the suspect shards of a stripe, by reconstructing
+/// sets of suspect shards (smallest first) until the stripe is consistent with its parity.
+///
+/// Returns the reconstructed shards, and the shards that were reconstructed.
+fn locate(
+    codec: &Codec,
+    shards: &[Vec<u8>],
+    known: &BTreeSet<usize>,
+    suspect: &[usize],
+    parity_shards: usize,
+) -> Option<(Vec<Vec<u8>>, BTreeSet<usize>)> {
+    // NOTE: At least one parity shard needs to be left to check the reconstruction
+    let max_size = parity_shards
+        .checked_sub(known.len() + 1)?
+        .min(suspect.len());
+
+    let mut attempts = 0;
+
+    for size in 0..=max_size {
+        let mut indices = (0..size).collect::<Vec<_>>();
+
+        loop {
+            attempts += 1;
+            if attempts > MAX_LOCATE_ATTEMPTS {
+                log::error!("Could not locate damaged shards in {MAX_LOCATE_ATTEMPTS} attempts");
+                return None;
+            }
+
+            let mut erased = known.clone();
+            erased.extend(indices.iter().filter_map(|idx| suspect.get(*idx)));
+
+            let present = (0..shards.len())
+                .map(|idx| !erased.contains(&idx))
+                .collect::<Vec<_>>();
+
+            let mut reconstructed = shards.to_vec();
+
+            if codec.reconstruct(&mut reconstructed, &present)
+                && codec.is_consistent(&reconstructed)
+            {
+                return Some((reconstructed, erased));
+            }
+
+            if !next_combination(&mut indices, suspect.len()) {
+                break;
+            }
+        }
+    }
+
+    None
+}
+
+/// Reconstructs the damaged shards of a stripe and writes them back.
+///
+/// Returns `false` if too many shards of the stripe are damaged.
+fn repair_stripe(
+    file: &mut File,
+    stream: &DataStream,
+    parity: &ParityRegion,
+    stripe: u64,
+    damaged: &DamagedShards,
+) -> std::io::Result<bool> {
+    let layout = parity.layout;
+    let data_shards = usize::from(layout.data_shards);
+    let parity_shards = usize::from(layout.parity_shards);
+    let shard_count = data_shards + parity_shards;
+
+    let shard_offset = |idx: usize| idx as u64 * u64::from(layout.shard_size);
+    let data_offset = stripe * layout.stripe_size();
+    let parity_idx = stripe * u64::from(layout.parity_shards);
+
+    let mut shards = vec![vec![0; layout.shard_size as usize]; shard_count];
+
+    for (idx, shard) in shards.iter_mut().enumerate() {
+        if damaged.known.contains(&idx) {
+            continue;
+        }
+
+        if idx < data_shards {
+            stream.read_at(&*file, data_offset + shard_offset(idx), shard)?;
+        } else {
+            file.read_at(
+                parity.shard_pos(parity_idx + (idx - data_shards) as u64),
+                shard,
+            )?;
+        }
+    }
+
+    let codec = layout.codec();
+    let suspect = damaged
+        .suspect
+        .difference(&damaged.known)
+        .copied()
+        .collect::<Vec<_>>();
+
+    // NOTE: If all shards that may be damaged can be reconstructed at once, there is no need to locate them
+    let (shards, erased) = if damaged.known.len() + suspect.len() <= parity_shards {
+        let erased = damaged
+            .known
+            .iter()
+            .chain(&suspect)
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        let present = (0..shard_count)
+            .map(|idx| !erased.contains(&idx))
+            .collect::<Vec<_>>();
+
+        if !codec.reconstruct(&mut shards, &present) {
+            return Ok(false);
+        }
+
+        (shards, erased)
+    } else if let Some(located) = locate(&codec, &shards, &damaged.known, &suspect, parity_shards) {
+        located
+    } else {
+        log::error!(
+            "Stripe {stripe} has too many damaged shards for {} parity shards",
+            layout.parity_shards,
+        );
+        return Ok(false);
+    };
+
+    for (idx, shard) in shards.iter().enumerate() {
+        if !erased.contains(&idx) {
+            continue;
+        }
+
+        if idx < data_shards {
+            stream.write_at(file, data_offset + shard_offset(idx), shard)?;
+        } else {
+            let pos = parity.shard_pos(parity_idx + (idx - data_shards) as u64);
+            file.seek(SeekFrom::Start(pos))?;
+            file.write_all(shard)?;
+        }
+    }
+
+    log::debug!("Repaired {} shards of stripe {stripe}", erased.len());
+
+    Ok(true)
+}
+
+/// Reads the parity region and checks it matches the table of contents.
+fn read_parity(
+    file: &mut File,
+    end: u64,
+    digest: Option<Checksum>,
+    stream: &DataStream,
+) -> crate::Result<ParityRegion> {
+    let parity = crate::parity::read(file, end, digest)?;
+
+    if parity.data_len != stream.len() {
+        log::error!(
+            "Parity region covers {} bytes, but sections have {} bytes",
+            parity.data_len,
+            stream.len(),
+        );
+        return Err(crate::Error::InvalidParity {
+            path: None,
+            offset: parity.pos,
+        });
+    }
+
+    Ok(parity)
+}
+
+/// Finds damaged sections and parity shards, and repairs them using the parity region
+/// ending at `parity_end` (if any), which needs to match the digest from the trailer.
+pub fn repair(
+    file: &mut File,
+    toc: &Toc,
+    block_index: &BlockIndex,
+    parity_end: Option<u64>,
+    parity_digest: Option<Checksum>,
+) -> crate::Result<RepairReport> {
+    log::debug!("Repairing archive");
+
+    let mut damage = find_damaged_sections(file, toc, block_index)?;
+
+    if let Some(end) = parity_end {
+        let stream = DataStream::new(toc);
+        let parity = read_parity(file, end, parity_digest, &stream)?;
+
+        damage.extend(find_damaged_parity(file, &parity)?);
+
+        let stripes = damaged_shards(&damage, &stream, &parity);
+
+        for (stripe, damaged) in &stripes {
+            repair_stripe(file, &stream, &parity, *stripe, damaged)?;
+        }
+
+        if !stripes.is_empty() {
+            file.sync_all()?;
+        }
+    }
+
+    // NOTE: Bytes only count as repaired if they match their checksum again
+    let regions = damage
+        .into_iter()
+        .map(
+            |Damage {
+                 mut region,
+                 expected,
+                 ..
+             }| {
+                region.repaired = check(file, region.pos, region.len, &expected)?;
+                Ok(region)
+            },
+        )
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    Ok(RepairReport { regions })
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{TocEntry, Writer};
+use std::io::Write;
+
+/// Writer for a single named section, returned by [`Writer::section`]
+///
+/// The section is closed when the section writer is finished or dropped.
+///
+/// The section writer is the only handle that can write data into an archive,
+/// as the [`Writer`] itself does not implement [`Write`]:
+///
+/// ```compile_fail
+/// use sfa::Writer;
+/// use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+///
+/// let mut writer = Writer::new_at_path(&path)?;
+/// writer.write_all(b"Outside of section")?;
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+///
+/// Because the section writer borrows the [`Writer`] mutably, no other section
+/// can be started while the section is open:
+///
+/// ```compile_fail
+/// use sfa::Writer;
+/// use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+///
+/// let mut writer = Writer::new_at_path(&path)?;
+/// let mut section = writer.section("Section 1")?;
+/// writer.section("Section 2")?;
+/// section.write_all(b"Hello world!\n")?;
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+pub struct SectionWriter<'a> {
+    writer: &'a mut Writer,
+    closed: bool,
+}
+
+impl<'a> SectionWriter<'a> {
+    pub(crate) fn new(writer: &'a mut Writer) -> Self {
+        Self {
+            writer,
+            closed: false,
+        }
+    }
+
+    /// Closes the section.
+    ///
+    /// Returns the table of contents entry of the section, including its length and checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn finish(mut self) -> std::io::Result<TocEntry> {
+        self.closed = true;
+
+        self.writer.finish_section()
+    }
+}
+
+impl Write for SectionWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write_section_data(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush_section_data()
+    }
+}
+
+impl Drop for SectionWriter<'_> {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+
+        if let Err(e) = self.writer.close_section() {
+            log::error!("Failed to close section: {e:?}");
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+pub use crate::trailer::writer::SIGNATURE_LEN;
+
+/// Signs the serialized table of contents and trailer.
+pub fn sign(key: &SigningKey, toc: &[u8], trailer: &[u8]) -> [u8; SIGNATURE_LEN] {
+    log::trace!("Signing ToC");
+
+    key.sign(&[toc, trailer].concat()).to_bytes()
+}
+
+/// Verifies the signature over the serialized table of contents and trailer against any of the given keys.
+pub fn verify(
+    keys: &[VerifyingKey],
+    toc: &[u8],
+    trailer: &[u8],
+    signature: &[u8; SIGNATURE_LEN],
+) -> crate::Result<()> {
+    log::trace!("Verifying ToC signature");
+
+    let message = [toc, trailer].concat();
+    let signature = Signature::from_bytes(signature);
+
+    if keys
+        .iter()
+        .any(|key| key.verify_strict(&message, &signature).is_ok())
+    {
+        Ok(())
+    } else {
+        log::error!("Invalid signature");
+        Err(crate::Error::InvalidSignature { path: None })
+    }
+}
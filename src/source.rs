@@ -0,0 +1,185 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// Source of archive bytes that supports positional reads
+///
+/// This maps directly to storage that is accessed by range, like object storage,
+/// so archives can be read without downloading them first.
+///
+/// Implemented for [`std::fs::File`], byte slices and byte vectors.
+/// On platforms other than Unix and Windows, files are read by seeking,
+/// so a file must not be read from multiple threads at once.
+pub trait RandomAccessSource {
+    /// Returns the size of the source in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    fn size(&self) -> std::io::Result<u64>;
+
+    /// Reads exactly `buf.len()` bytes, starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the range exceeds the source.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()>;
+}
+
+impl<S: RandomAccessSource + ?Sized> RandomAccessSource for &S {
+    fn size(&self) -> std::io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+impl RandomAccessSource for [u8] {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        let bytes = usize::try_from(offset)
+            .ok()
+            .and_then(|start| self.get(start..start.checked_add(buf.len())?))
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+
+        buf.copy_from_slice(bytes);
+
+        Ok(())
+    }
+}
+
+impl RandomAccessSource for Vec<u8> {
+    fn size(&self) -> std::io::Result<u64> {
+        self.as_slice().size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        self.as_slice().read_at(offset, buf)
+    }
+}
+
+impl RandomAccessSource for std::fs::File {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    #[cfg(unix)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, mut offset: u64, mut buf: &mut [u8]) -> std::io::Result<()> {
+        while !buf.is_empty() {
+            match std::os::windows::fs::FileExt::seek_read(self, buf, offset) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)),
+                Ok(n) => {
+                    #[allow(clippy::indexing_slicing)]
+                    {
+                        buf = &mut buf[n..];
+                    }
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    // NOTE: Without positional reads, the cursor of the file is moved, and restored afterwards,
+    // because writers read back from the file they are appending to
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        let mut file = self;
+        let pos = file.stream_position()?;
+
+        file.seek(SeekFrom::Start(offset))?;
+        let result = file.read_exact(buf);
+        file.seek(SeekFrom::Start(pos))?;
+
+        result
+    }
+}
+
+/// Adapter to read a range of a [`RandomAccessSource`] using [`Read`] and [`Seek`]
+///
+/// Each read is mapped to a single positional read.
+pub struct SourceReader<S: RandomAccessSource> {
+    source: S,
+
+    /// Current position, relative to `start`
+    pos: u64,
+
+    start: u64,
+    len: u64,
+}
+
+impl<S: RandomAccessSource> SourceReader<S> {
+    /// Creates a reader over the whole source.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the source size could not be determined.
+    pub fn new(source: S) -> std::io::Result<Self> {
+        let len = source.size()?;
+        Ok(Self::with_range(source, 0, len))
+    }
+
+    /// Creates a reader over `len` bytes of the source, starting at `start`.
+    pub fn with_range(source: S, start: u64, len: u64) -> Self {
+        Self {
+            source,
+            pos: 0,
+            start,
+            len,
+        }
+    }
+}
+
+impl<S: RandomAccessSource> Read for SourceReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        #[allow(clippy::cast_possible_truncation)]
+        let n = self.len.saturating_sub(self.pos).min(buf.len() as u64) as usize;
+
+        if n == 0 {
+            return Ok(0);
+        }
+
+        #[allow(clippy::indexing_slicing)]
+        self.source.read_at(self.start + self.pos, &mut buf[..n])?;
+
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl<S: RandomAccessSource> Seek for SourceReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(pos) = pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        self.pos = pos;
+
+        Ok(pos)
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// Reader that speculatively reads the tail of the underlying reader in one go
+///
+/// The trailer and table of contents are located at the end of the archive,
+/// so for most archives they can be parsed without any further reads.
+/// Reads before the buffered tail are passed through to the underlying reader.
+pub struct TailReader<R: Read + Seek> {
+    inner: R,
+    tail: Vec<u8>,
+
+    /// Position of the buffered tail
+    tail_pos: u64,
+
+    /// Current (logical) position
+    pos: u64,
+}
+
+impl<R: Read + Seek> TailReader<R> {
+    pub fn new(mut inner: R, tail_size: u64) -> std::io::Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        let tail_pos = len.saturating_sub(tail_size);
+
+        log::trace!("Reading {} tail bytes at {tail_pos}", len - tail_pos);
+
+        // NOTE: The tail is bounded by the file size
+        #[allow(clippy::cast_possible_truncation)]
+        let mut tail = vec![0; (len - tail_pos) as usize];
+        inner.seek(SeekFrom::Start(tail_pos))?;
+        inner.read_exact(&mut tail)?;
+
+        Ok(Self {
+            inner,
+            tail,
+            tail_pos,
+            pos: tail_pos,
+        })
+    }
+
+    fn len(&self) -> u64 {
+        self.tail_pos + self.tail.len() as u64
+    }
+}
+
+impl<R: Read + Seek> Read for TailReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = if let Some(offset) = self.pos.checked_sub(self.tail_pos) {
+            #[allow(clippy::cast_possible_truncation)]
+            let available = self.tail.get(offset as usize..).unwrap_or_default();
+
+            let n = available.len().min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            buf[..n].copy_from_slice(&available[..n]);
+
+            n
+        } else {
+            log::trace!("Reading before tail at {}", self.pos);
+
+            #[allow(clippy::cast_possible_truncation)]
+            let max = (self.tail_pos - self.pos).min(buf.len() as u64) as usize;
+
+            self.inner.seek(SeekFrom::Start(self.pos))?;
+
+            #[allow(clippy::indexing_slicing)]
+            self.inner.read(&mut buf[..max])?
+        };
+
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for TailReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(pos) = pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        self.pos = pos;
+
+        Ok(pos)
+    }
+}
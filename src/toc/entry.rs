@@ -2,6 +2,7 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+use crate::{Checksum, ChecksumType, RandomAccessSource, SourceReader};
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
 use std::{
@@ -13,11 +14,15 @@ use std::{
 pub type SectionName = Vec<u8>;
 
 /// Entry in the table of contents (a section in the archive)
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TocEntry {
     pub(crate) name: SectionName,
     pub(crate) pos: u64,
     pub(crate) len: u64,
+    pub(crate) checksum: Option<Checksum>,
+
+    /// `true` if the section shares the bytes of another section
+    pub(crate) alias: bool,
 }
 
 impl TocEntry {
@@ -40,6 +45,22 @@ impl TocEntry {
         self.len
     }
 
+    /// Returns `true` if the section shares the bytes of another section,
+    /// e.g. because it was added using [`Writer::alias`](crate::Writer::alias)
+    /// or deduplicated (see [`Writer::use_dedup`](crate::Writer::use_dedup)).
+    #[must_use]
+    pub fn is_alias(&self) -> bool {
+        self.alias
+    }
+
+    /// Returns the section checksum.
+    ///
+    /// Archives written with format version 1 do not contain section checksums.
+    #[must_use]
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.checksum
+    }
+
     #[doc(hidden)]
     pub fn buf_reader(&self, path: &Path) -> std::io::Result<impl std::io::BufRead> {
         let mut file = BufReader::new(File::open(path)?);
@@ -47,7 +68,15 @@ impl TocEntry {
         Ok(file.take(self.len))
     }
 
-    pub(crate) fn write_into(&self, mut writer: impl Write) -> crate::Result<()> {
+    /// Returns a reader over the section's bytes in the given source.
+    ///
+    /// Each read is mapped to a single [`RandomAccessSource::read_at`], so wrap the reader
+    /// in a [`std::io::BufReader`] if the source has a high per-read cost.
+    pub fn reader<S: RandomAccessSource>(&self, source: S) -> SourceReader<S> {
+        SourceReader::with_range(source, self.pos, self.len)
+    }
+
+    pub(crate) fn write_into(&self, mut writer: impl Write) -> std::io::Result<()> {
         use byteorder::LE;
 
         writer.write_u64::<LE>(self.pos())?;
@@ -59,19 +88,52 @@ impl TocEntry {
         )?;
         writer.write_all(self.name())?;
 
+        #[allow(clippy::expect_used)]
+        writer.write_all(
+            self.checksum
+                .as_ref()
+                .expect("section checksum should be set by writer")
+                .as_bytes(),
+        )?;
+
         Ok(())
     }
 
-    pub(crate) fn read_from_file(reader: &mut impl Read) -> crate::Result<Self> {
+    pub(crate) fn read_from_file(
+        reader: &mut impl Read,
+        checksum_type: Option<ChecksumType>,
+    ) -> crate::Result<Self> {
         use byteorder::LE;
 
         let pos = reader.read_u64::<LE>()?;
         let len = reader.read_u64::<LE>()?;
         let section_name_len = reader.read_u16::<LE>()?;
 
-        let mut name = vec![0; section_name_len as usize];
-        reader.read_exact(&mut name)?;
+        // NOTE: The name length is untrusted, so grow the buffer as the name is read
+        let mut name = vec![];
+        reader
+            .take(u64::from(section_name_len))
+            .read_to_end(&mut name)?;
+
+        if name.len() != usize::from(section_name_len) {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let checksum = match checksum_type {
+            Some(checksum_type) => {
+                let mut digest = vec![0; checksum_type.digest_len()];
+                reader.read_exact(&mut digest)?;
+                Some(Checksum::from_bytes(checksum_type, &digest))
+            }
+            None => None,
+        };
 
-        Ok(Self { name, pos, len })
+        Ok(Self {
+            name,
+            pos,
+            len,
+            checksum,
+            alias: false,
+        })
     }
 }
@@ -4,78 +4,225 @@
 
 use super::writer::TOC_MAGIC;
 use crate::{
-    checksum::Checksum,
+    checksum::{ChecksumType, Hasher},
     toc::{entry::TocEntry, Toc},
-    Result,
+    trailer::reader::ParsedTrailer,
+    ReaderOptions, Result,
 };
 use byteorder::ReadBytesExt;
 use std::io::{Read, Seek, SeekFrom};
 
-struct ChecksummedReader<R: std::io::Read> {
-    inner: R,
-    hasher: xxhash_rust::xxh3::Xxh3Default,
-}
+pub struct TocReader;
 
-impl<R: std::io::Read> ChecksummedReader<R> {
-    pub fn new(reader: R) -> Self {
-        Self {
-            inner: reader,
-            hasher: xxhash_rust::xxh3::Xxh3Default::new(),
-        }
+impl TocReader {
+    pub fn from_reader<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> Result<Toc> {
+        let bytes = Self::read_bytes(reader, trailer, options)?;
+        Self::parse(&bytes, trailer, options)
     }
 
-    pub fn checksum(&self) -> Checksum {
-        Checksum::from_raw(self.hasher.digest128())
+    /// Reads the raw table of contents, after checking it ends where the trailer (or signature) begins.
+    pub fn read_bytes<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> Result<Vec<u8>> {
+        log::trace!("Reading ToC");
+
+        if trailer.toc_len > options.max_toc_size {
+            log::error!(
+                "ToC length {} exceeds limit of {}",
+                trailer.toc_len,
+                options.max_toc_size,
+            );
+            return Err(crate::Error::TocTooLarge {
+                path: None,
+                offset: trailer.toc_pos,
+                len: trailer.toc_len,
+                limit: options.max_toc_size,
+            });
+        }
+
+        if trailer.toc_pos.checked_add(trailer.toc_len) != Some(trailer.toc_end()) {
+            log::error!(
+                "ToC at {} with length {} does not end at {}",
+                trailer.toc_pos,
+                trailer.toc_len,
+                trailer.toc_end(),
+            );
+            return Err(crate::Error::TocOutOfBounds {
+                path: None,
+                offset: trailer.toc_pos,
+                len: trailer.toc_len,
+                expected_end: trailer.toc_end(),
+            });
+        }
+
+        reader.seek(SeekFrom::Start(trailer.toc_pos))?;
+
+        // NOTE: The ToC is located before the trailer, so its length is bounded by the file size
+        #[allow(clippy::cast_possible_truncation)]
+        let mut bytes = vec![0; trailer.toc_len as usize];
+        reader.read_exact(&mut bytes)?;
+
+        Ok(bytes)
     }
-}
 
-impl<R: std::io::Read> std::io::Read for ChecksummedReader<R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let n = self.inner.read(buf)?;
+    /// Verifies and parses the raw table of contents.
+    pub fn parse(bytes: &[u8], trailer: &ParsedTrailer, options: &ReaderOptions) -> Result<Toc> {
+        Self::verify_checksum(bytes, trailer)?;
+
+        let (entries, consumed) = Self::parse_lenient(bytes, trailer, options)?;
 
-        #[allow(clippy::indexing_slicing)]
-        self.hasher.update(&buf[..n]);
+        if consumed != bytes.len() {
+            log::error!("ToC has {} trailing bytes", bytes.len() - consumed);
+            return Err(crate::Error::TocLengthMismatch {
+                path: None,
+                offset: trailer.toc_pos,
+                expected: trailer.toc_len,
+                got: consumed as u64,
+            });
+        }
+
+        if let Some(entry) = entries.iter().find(|entry| {
+            entry
+                .pos
+                .checked_add(entry.len)
+                .is_none_or(|end| end > trailer.toc_pos)
+        }) {
+            log::error!(
+                "Section {:?} at {} with length {} exceeds ToC at {}",
+                entry.name,
+                entry.pos,
+                entry.len,
+                trailer.toc_pos,
+            );
+            return Err(crate::Error::SectionOutOfBounds {
+                path: None,
+                section: entry.name.clone(),
+                offset: entry.pos,
+                len: entry.len,
+                toc_pos: trailer.toc_pos,
+            });
+        }
 
-        Ok(n)
+        Ok(Toc(entries))
     }
-}
 
-pub struct TocReader;
+    /// Verifies the raw table of contents against the trailer's checksum.
+    pub fn verify_checksum(bytes: &[u8], trailer: &ParsedTrailer) -> Result<()> {
+        let mut hasher = Hasher::new(trailer.toc_checksum.checksum_type());
+        hasher.update(bytes);
+        hasher
+            .checksum()
+            .check(trailer.toc_checksum, None, trailer.toc_pos)
+    }
 
-impl TocReader {
-    pub fn from_reader<R: Read + Seek>(
-        reader: &mut R,
-        toc_pos: u64,
-        toc_checksum: Checksum,
-    ) -> Result<Toc> {
-        use byteorder::LE;
+    /// Parses the raw table of contents, without checking the entries are in bounds,
+    /// or all bytes are used.
+    ///
+    /// Returns the entries and the number of bytes used by them.
+    pub fn parse_lenient(
+        bytes: &[u8],
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> Result<(Vec<TocEntry>, usize)> {
+        let mut reader = bytes;
+
+        let entries = Self::parse_entries(&mut reader, trailer, options).map_err(|e| match e {
+            crate::Error::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                log::error!("ToC is truncated");
+                crate::Error::TruncatedToc {
+                    path: None,
+                    offset: trailer.toc_pos,
+                    len: trailer.toc_len,
+                }
+            }
+            e => e,
+        })?;
 
-        log::trace!("Reading ToC");
+        Ok((entries, bytes.len() - reader.len()))
+    }
 
-        reader.seek(SeekFrom::Start(toc_pos))?;
+    fn parse_entries(
+        reader: &mut &[u8],
+        trailer: &ParsedTrailer,
+        options: &ReaderOptions,
+    ) -> Result<Vec<TocEntry>> {
+        use byteorder::LE;
 
-        let mut reader = ChecksummedReader::new(reader);
+        let section_checksum_type = trailer
+            .has_section_checksums()
+            .then_some(trailer.toc_checksum.checksum_type());
 
         {
             let mut buf = [0u8; TOC_MAGIC.len()];
             reader.read_exact(&mut buf)?;
 
             if buf != TOC_MAGIC {
-                log::error!("Invalid version");
-                return Err(crate::Error::InvalidVersion);
+                log::error!("Invalid ToC header");
+                return Err(crate::Error::InvalidTocMagic {
+                    path: None,
+                    offset: trailer.toc_pos,
+                });
             }
         }
 
         let len = reader.read_u32::<LE>()?;
 
-        let mut entries = Vec::with_capacity(len as usize);
+        if len > options.max_entry_count {
+            log::error!(
+                "ToC entry count {len} exceeds limit of {}",
+                options.max_entry_count,
+            );
+            return Err(crate::Error::TooManySections {
+                path: None,
+                offset: trailer.toc_pos,
+                count: len,
+                limit: options.max_entry_count,
+            });
+        }
+
+        // NOTE: The entry count is untrusted, so only preallocate
+        // as many entries as the remaining bytes can hold
+        let min_entry_size = 8 + 8 + 2 + section_checksum_type.map_or(0, ChecksumType::digest_len);
+        let mut entries = Vec::with_capacity((len as usize).min(reader.len() / min_entry_size));
 
         for _ in 0..len {
-            entries.push(TocEntry::read_from_file(&mut reader)?);
+            let entry_offset =
+                trailer.toc_pos + trailer.toc_len.saturating_sub(reader.len() as u64);
+            let entry = TocEntry::read_from_file(reader, section_checksum_type)?;
+
+            if entry.name.len() > usize::from(options.max_name_len) {
+                log::error!(
+                    "Section name length {} exceeds limit of {}",
+                    entry.name.len(),
+                    options.max_name_len,
+                );
+                return Err(crate::Error::SectionNameTooLong {
+                    path: None,
+                    offset: entry_offset,
+                    len: entry.name.len(),
+                    limit: options.max_name_len,
+                });
+            }
+
+            entries.push(entry);
         }
 
-        reader.checksum().check(toc_checksum)?;
+        if trailer.has_aliases() {
+            for chunk in entries.chunks_mut(8) {
+                let byte = reader.read_u8()?;
 
-        Ok(Toc(entries))
+                for (bit, entry) in chunk.iter_mut().enumerate() {
+                    entry.alias = byte & (1 << bit) != 0;
+                }
+            }
+        }
+
+        Ok(entries)
     }
 }
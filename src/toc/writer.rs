@@ -2,7 +2,11 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{checksum::Checksum, checksum_writer::ChecksummedWriter, toc::entry::TocEntry};
+use crate::{
+    checksum::{Checksum, ChecksumType},
+    checksum_writer::ChecksummedWriter,
+    toc::entry::TocEntry,
+};
 use byteorder::WriteBytesExt;
 use std::io::Write;
 
@@ -11,13 +15,17 @@ pub const TOC_MAGIC: &[u8] = b"TOC!";
 pub struct TocWriter;
 
 impl TocWriter {
-    pub fn write_into(mut writer: impl Write, entries: &[TocEntry]) -> crate::Result<Checksum> {
+    pub fn write_into(
+        mut writer: impl Write,
+        entries: &[TocEntry],
+        checksum_type: ChecksumType,
+    ) -> crate::Result<Checksum> {
         use byteorder::LE;
 
         log::trace!("Writing ToC");
         log::trace!("ToC: {entries:#?}");
 
-        let mut writer = ChecksummedWriter::new(&mut writer);
+        let mut writer = ChecksummedWriter::new(&mut writer, checksum_type);
 
         writer.write_all(TOC_MAGIC)?;
         writer.write_u32::<LE>(
@@ -30,6 +38,18 @@ impl TocWriter {
             entry.write_into(&mut writer)?;
         }
 
+        // NOTE: Aliases are marked in a bitmap after the entries (see `FLAG_ALIASES`)
+        if entries.iter().any(TocEntry::is_alias) {
+            for chunk in entries.chunks(8) {
+                let byte = chunk
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| entry.is_alias())
+                    .fold(0u8, |byte, (bit, _)| byte | (1 << bit));
+                writer.write_u8(byte)?;
+            }
+        }
+
         Ok(writer.checksum())
     }
 }
@@ -2,37 +2,485 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::writer::TRAILER_MAGIC;
-use crate::{checksum::Checksum, Result};
+use super::writer::{
+    FLAG_BLOCK_CHECKSUMS, FLAG_MERKLE_ROOT, FLAG_PARITY, FLAG_SIGNED, MIRROR_ALIGNMENT,
+    SIGNATURE_LEN, TRAILER_MAGIC, TRAILER_VERSION, TRAILER_VERSION_EXTENDED,
+};
+use crate::{
+    checksum::{Checksum, ChecksumType, MAX_DIGEST_LEN},
+    toc::writer::TOC_MAGIC,
+    Result,
+};
 use byteorder::ReadBytesExt;
 use std::io::{Read, Seek, SeekFrom};
 
+/// Size of the version 1 trailer, which starts with the magic bytes
+/// and only supports XXH3
 #[allow(clippy::cast_possible_wrap)]
-const TRAILER_SIZE: i64 = TRAILER_MAGIC.len() as i64 + 1 + 1 + 16 + 8 + 8;
+const TRAILER_V1_SIZE: i64 = TRAILER_MAGIC.len() as i64 + 1 + 1 + 16 + 8 + 8;
+
+/// Size of the trailer fields that are covered by the trailer checksum
+const TRAILER_CHECKSUMMED_SIZE: usize = MAX_DIGEST_LEN + 8 + 8 + 1 + 1;
+
+/// Size of the current trailer, which ends with the magic bytes
+pub const TRAILER_SIZE: usize = TRAILER_CHECKSUMMED_SIZE + 8 + 1 + TRAILER_MAGIC.len();
+
+/// Number of digests stored in a version 3 trailer
+const TRAILER_V3_DIGESTS: usize = 3;
+
+/// Size of the version 3 trailer, which additionally contains the Merkle root
+/// and the digests of the block index and parity region
+pub const TRAILER_V3_SIZE: usize = TRAILER_SIZE + TRAILER_V3_DIGESTS * MAX_DIGEST_LEN;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct ParsedTrailer {
+    /// Position of the trailer in the file
+    pub pos: u64,
+
+    pub version: u8,
     pub toc_checksum: Checksum,
     pub toc_pos: u64,
+    pub toc_len: u64,
+    pub flags: u8,
+
+    /// Merkle root over the section checksums (version 3 only)
+    pub merkle_root: Option<Checksum>,
+
+    /// Digest of the block index (version 3 only)
+    pub block_index_digest: Option<Checksum>,
+
+    /// Digest of the parity region (version 3 only)
+    pub parity_digest: Option<Checksum>,
+}
+
+impl ParsedTrailer {
+    /// Returns `true` if the table of contents entries contain section checksums.
+    pub fn has_section_checksums(&self) -> bool {
+        self.version >= 0x2
+    }
+
+    pub fn has_section_markers(&self) -> bool {
+        self.flags & super::writer::FLAG_SECTION_MARKERS != 0
+    }
+
+    pub fn has_toc_mirror(&self) -> bool {
+        self.flags & super::writer::FLAG_TOC_MIRROR != 0
+    }
+
+    pub fn has_block_checksums(&self) -> bool {
+        self.flags & super::writer::FLAG_BLOCK_CHECKSUMS != 0
+    }
+
+    pub fn has_parity(&self) -> bool {
+        self.flags & super::writer::FLAG_PARITY != 0
+    }
+
+    pub fn has_aliases(&self) -> bool {
+        self.flags & super::writer::FLAG_ALIASES != 0
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.flags & super::writer::FLAG_SIGNED != 0
+    }
+
+    /// Returns the size of the trailer in bytes.
+    pub fn size(&self) -> u64 {
+        match self.version {
+            0x1 => TRAILER_V1_SIZE.unsigned_abs(),
+            TRAILER_VERSION_EXTENDED => TRAILER_V3_SIZE as u64,
+            _ => TRAILER_SIZE as u64,
+        }
+    }
+
+    /// Returns the position where the table of contents is expected to end.
+    pub fn toc_end(&self) -> u64 {
+        if self.is_signed() {
+            self.pos.saturating_sub(SIGNATURE_LEN as u64)
+        } else {
+            self.pos
+        }
+    }
 }
 
 pub struct TrailerReader;
 
 impl TrailerReader {
     pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<ParsedTrailer> {
+        log::trace!("Reading trailer");
+
+        let len = reader.seek(SeekFrom::End(0))?;
+
+        if len < TRAILER_MAGIC.len() as u64 {
+            log::error!("File is too small to contain a trailer");
+            return Err(crate::Error::IncompleteArchive { path: None, len });
+        }
+
+        #[allow(clippy::cast_possible_wrap)]
+        reader.seek(SeekFrom::End(-(TRAILER_MAGIC.len() as i64)))?;
+
+        let mut buf = [0u8; TRAILER_MAGIC.len()];
+        reader.read_exact(&mut buf)?;
+
+        // NOTE: Version 1 trailers end with the upper bytes of the ToC length,
+        // which are zero for any realistic ToC
+        if buf == TRAILER_MAGIC {
+            return Self::read_current(reader, len);
+        }
+
+        let result = Self::read_v1(reader, len);
+
+        // NOTE: Only report an incomplete archive if there is no trailer at all,
+        // so finished archives are not mistaken for unfinished ones
+        if matches!(result, Err(crate::Error::IncompleteArchive { .. })) {
+            if let Some((pos, expected_len)) = Self::find_damaged(reader, len)? {
+                if expected_len == len {
+                    log::error!("Trailer magic is corrupted");
+                    return Err(crate::Error::InvalidTrailerMagic {
+                        path: None,
+                        offset: pos,
+                    });
+                }
+
+                log::error!("Archive is truncated: expected {expected_len} bytes, got {len}");
+                return Err(crate::Error::TruncatedArchive {
+                    path: None,
+                    len,
+                    expected_len,
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Looks for a current trailer whose magic bytes are corrupted or cut off, returning
+    /// its position and the file size it implies, if the table of contents it points to
+    /// ends right before it.
+    ///
+    /// This tells finished archives with a damaged or truncated end apart from archives
+    /// that were never finished, which end with section data.
+    fn find_damaged<R: Read + Seek>(reader: &mut R, len: u64) -> Result<Option<(u64, u64)>> {
         use byteorder::LE;
 
-        log::trace!("Reading trailer");
+        // NOTE: The ToC position, length and flags need to be intact
+        const FIELDS_END: usize = MAX_DIGEST_LEN + 8 + 8 + 1;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut tail = vec![0; len.min(TRAILER_V3_SIZE as u64) as usize];
+        let tail_pos = len - tail.len() as u64;
+        reader.seek(SeekFrom::Start(tail_pos))?;
+        reader.read_exact(&mut tail)?;
+
+        for size in [TRAILER_SIZE, TRAILER_V3_SIZE] {
+            for missing in 0..=(size - FIELDS_END) {
+                let Some(pos) = (len + missing as u64).checked_sub(size as u64) else {
+                    continue;
+                };
+
+                // NOTE: `pos` is at most `FIELDS_END` bytes before the end of the file
+                #[allow(clippy::cast_possible_truncation)]
+                let Some(mut fields) = pos
+                    .checked_sub(tail_pos)
+                    .and_then(|offset| tail.get(offset as usize + MAX_DIGEST_LEN..))
+                else {
+                    continue;
+                };
+
+                let toc_pos = fields.read_u64::<LE>()?;
+                let toc_len = fields.read_u64::<LE>()?;
+                let flags = fields.read_u8()?;
+
+                let toc_end = if flags & FLAG_SIGNED == 0 {
+                    pos
+                } else {
+                    pos.saturating_sub(SIGNATURE_LEN as u64)
+                };
+
+                if toc_len < TOC_MAGIC.len() as u64 || toc_pos.checked_add(toc_len) != Some(toc_end)
+                {
+                    continue;
+                }
+
+                let mut buf = [0u8; TOC_MAGIC.len()];
+                reader.seek(SeekFrom::Start(toc_pos))?;
+                reader.read_exact(&mut buf)?;
+
+                if buf == TOC_MAGIC {
+                    return Ok(Some((pos, pos + size as u64)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the mirrored trailer, which is located before the (primary) table of contents.
+    pub fn read_mirror<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+    ) -> Result<ParsedTrailer> {
+        log::trace!("Reading mirrored trailer");
+
+        let end = Self::mirror_end(reader, trailer)?;
+        Self::read_current(reader, end)
+    }
+
+    /// Returns the position where the mirrored trailer ends, skipping the zero padding
+    /// between it and the (primary) table of contents.
+    pub fn mirror_end<R: Read + Seek>(reader: &mut R, trailer: &ParsedTrailer) -> Result<u64> {
+        Self::padding_start(reader, trailer.toc_pos)
+    }
+
+    /// Returns the position where the zero padding before the (primary) table of contents
+    /// at `toc_pos` starts.
+    fn padding_start<R: Read + Seek>(reader: &mut R, toc_pos: u64) -> Result<u64> {
+        let padding_pos = toc_pos.saturating_sub(MIRROR_ALIGNMENT - 1);
+
+        // NOTE: The padding is shorter than the alignment
+        #[allow(clippy::cast_possible_truncation)]
+        let mut buf = vec![0; (toc_pos - padding_pos) as usize];
+        reader.seek(SeekFrom::Start(padding_pos))?;
+        reader.read_exact(&mut buf)?;
+
+        let padding = buf.iter().rev().take_while(|byte| **byte == 0).count();
+
+        Ok(toc_pos - padding as u64)
+    }
+
+    /// Looks for the mirrored trailer, for when the (primary) trailer cannot be read,
+    /// and returns the (primary) trailer derived from it.
+    ///
+    /// The (primary) table of contents of an archive with a mirror starts at a multiple of
+    /// [`MIRROR_ALIGNMENT`], so only aligned positions that start with the table of contents
+    /// magic bytes are checked, going back at most `max_toc_size` bytes (plus the size of
+    /// the signature and trailer) from the end of the file.
+    /// A mirrored trailer is only accepted if it is followed by exactly the bytes needed for
+    /// the (primary) table of contents, signature (if any) and trailer, so trailers of archives
+    /// stored in sections are skipped.
+    pub fn find_mirror<R: Read + Seek>(
+        reader: &mut R,
+        max_toc_size: u64,
+    ) -> Result<Option<ParsedTrailer>> {
+        log::debug!("Searching for mirrored trailer");
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        let min_toc_pos = len
+            .saturating_sub(
+                max_toc_size.saturating_add(SIGNATURE_LEN as u64 + TRAILER_V3_SIZE as u64),
+            )
+            // NOTE: A ToC at the start of the file has no mirror before it
+            .max(MIRROR_ALIGNMENT);
+
+        let mut toc_pos =
+            len.saturating_sub(TOC_MAGIC.len() as u64) / MIRROR_ALIGNMENT * MIRROR_ALIGNMENT;
+
+        while toc_pos >= min_toc_pos {
+            if let Some(trailer) = Self::read_aligned_mirror(reader, len, toc_pos)? {
+                log::warn!("Using mirrored trailer before {toc_pos}");
+                return Ok(Some(trailer));
+            }
+
+            toc_pos -= MIRROR_ALIGNMENT;
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the mirrored trailer before the (primary) table of contents at `toc_pos`,
+    /// returning the (primary) trailer derived from it, if it is consistent with the file.
+    fn read_aligned_mirror<R: Read + Seek>(
+        reader: &mut R,
+        len: u64,
+        toc_pos: u64,
+    ) -> Result<Option<ParsedTrailer>> {
+        let mut magic = [0u8; TOC_MAGIC.len()];
+        reader.seek(SeekFrom::Start(toc_pos))?;
+        reader.read_exact(&mut magic)?;
+
+        if magic != TOC_MAGIC {
+            return Ok(None);
+        }
+
+        let end = Self::padding_start(reader, toc_pos)?;
+
+        let Ok(mirror) = Self::read_current(reader, end) else {
+            return Ok(None);
+        };
+
+        if !mirror.has_toc_mirror()
+            || mirror.toc_pos.checked_add(mirror.toc_len) != Some(mirror.pos)
+        {
+            return Ok(None);
+        }
+
+        let unsigned_len = toc_pos
+            .saturating_add(mirror.toc_len)
+            .saturating_add(mirror.size());
+
+        let flags = if len == unsigned_len {
+            mirror.flags
+        } else if len == unsigned_len.saturating_add(SIGNATURE_LEN as u64) {
+            mirror.flags | FLAG_SIGNED
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(ParsedTrailer {
+            pos: len - mirror.size(),
+            toc_pos,
+            flags,
+            ..mirror
+        }))
+    }
+
+    /// Reads a current (version 2 or 3) trailer that ends at the given position.
+    ///
+    /// The version is read first, as it determines the size of the trailer.
+    pub fn read_current<R: Read + Seek>(reader: &mut R, end: u64) -> Result<ParsedTrailer> {
+        use byteorder::LE;
+
+        let invalid_position = || {
+            log::error!("Invalid trailer position");
+            crate::Error::InvalidTrailerMagic {
+                path: None,
+                offset: end,
+            }
+        };
+
+        let version_pos = end
+            .checked_sub(TRAILER_MAGIC.len() as u64 + 1)
+            .ok_or_else(invalid_position)?;
+
+        reader.seek(SeekFrom::Start(version_pos))?;
+        let version = reader.read_u8()?;
+
+        let size = match version {
+            TRAILER_VERSION => TRAILER_SIZE,
+            TRAILER_VERSION_EXTENDED => TRAILER_V3_SIZE,
+            _ => {
+                log::error!("Invalid version");
+                return Err(crate::Error::UnsupportedVersion {
+                    path: None,
+                    offset: version_pos,
+                    version,
+                });
+            }
+        };
+
+        let pos = end.checked_sub(size as u64).ok_or_else(invalid_position)?;
+
+        reader.seek(SeekFrom::Start(pos))?;
+
+        let mut buf = [0u8; TRAILER_V3_SIZE];
+
+        #[allow(clippy::indexing_slicing)]
+        let buf = &mut buf[..size];
+        reader.read_exact(buf)?;
+
+        let mut reader = &buf[..];
+
+        let mut digest = [0u8; MAX_DIGEST_LEN];
+        reader.read_exact(&mut digest)?;
+
+        let toc_pos = reader.read_u64::<LE>()?;
+        let toc_len = reader.read_u64::<LE>()?;
+        let flags = reader.read_u8()?;
+        let checksum_type = reader.read_u8()?;
+
+        let mut digests = [[0u8; MAX_DIGEST_LEN]; TRAILER_V3_DIGESTS];
+
+        if version == TRAILER_VERSION_EXTENDED {
+            for digest in &mut digests {
+                reader.read_exact(digest)?;
+            }
+        }
+
+        let checksummed_size = size - 8 - 1 - TRAILER_MAGIC.len();
+        let trailer_checksum = reader.read_u64::<LE>()?;
+
+        // NOTE: Skip version, which was already read
+        reader.read_u8()?;
+
+        if reader != TRAILER_MAGIC {
+            log::error!("Invalid trailer header");
+            return Err(crate::Error::InvalidTrailerMagic {
+                path: None,
+                offset: pos,
+            });
+        }
+
+        {
+            #[allow(clippy::indexing_slicing)]
+            let got = xxhash_rust::xxh3::xxh3_64(&buf[..checksummed_size]);
+
+            if got != trailer_checksum {
+                log::error!("Trailer checksum mismatch: expected {trailer_checksum}, got {got}");
+                return Err(crate::Error::TrailerChecksumMismatch {
+                    path: None,
+                    offset: pos,
+                    got,
+                    expected: trailer_checksum,
+                });
+            }
+        }
+
+        let Some(checksum_type) = ChecksumType::from_id(checksum_type) else {
+            log::error!("Invalid checksum type");
+            return Err(crate::Error::UnsupportedChecksumType {
+                path: None,
+                offset: pos,
+                checksum_type,
+            });
+        };
+
+        #[allow(clippy::indexing_slicing)]
+        let toc_checksum =
+            Checksum::from_bytes(checksum_type, &digest[..checksum_type.digest_len()]);
+
+        // NOTE: The digest slots of a version 3 trailer are zero-padded if unused,
+        // so a digest is only set if its flag is set as well
+        let [merkle_root_digest, block_index_digest, parity_digest] = digests;
+        let stored_digest = |flag: u8, digest: &[u8; MAX_DIGEST_LEN]| {
+            #[allow(clippy::indexing_slicing)]
+            (version == TRAILER_VERSION_EXTENDED && flags & flag != 0)
+                .then(|| Checksum::from_bytes(checksum_type, &digest[..checksum_type.digest_len()]))
+        };
+        let merkle_root = stored_digest(FLAG_MERKLE_ROOT, &merkle_root_digest);
+        let block_index_digest = stored_digest(FLAG_BLOCK_CHECKSUMS, &block_index_digest);
+        let parity_digest = stored_digest(FLAG_PARITY, &parity_digest);
+
+        Ok(ParsedTrailer {
+            pos,
+            version,
+            toc_checksum,
+            toc_pos,
+            toc_len,
+            flags,
+            merkle_root,
+            block_index_digest,
+            parity_digest,
+        })
+    }
+
+    fn read_v1<R: Read + Seek>(reader: &mut R, len: u64) -> Result<ParsedTrailer> {
+        use byteorder::LE;
+
+        // NOTE: Neither a current nor a version 1 trailer ends the file,
+        // so the archive was (most likely) never finished
+        if len < TRAILER_V1_SIZE.unsigned_abs() {
+            log::error!("File does not end with a trailer");
+            return Err(crate::Error::IncompleteArchive { path: None, len });
+        }
 
-        reader.seek(SeekFrom::End(-TRAILER_SIZE))?;
+        let pos = reader.seek(SeekFrom::End(-TRAILER_V1_SIZE))?;
 
         {
             let mut buf = [0u8; TRAILER_MAGIC.len()];
             reader.read_exact(&mut buf)?;
 
             if buf != TRAILER_MAGIC {
-                log::error!("Invalid trailer header");
-                return Err(crate::Error::InvalidHeader);
+                log::error!("File does not end with a trailer");
+                return Err(crate::Error::IncompleteArchive { path: None, len });
             }
         }
 
@@ -40,7 +488,11 @@ impl TrailerReader {
             let version = reader.read_u8()?;
             if version != 0x1 {
                 log::error!("Invalid version");
-                return Err(crate::Error::InvalidVersion);
+                return Err(crate::Error::UnsupportedVersion {
+                    path: None,
+                    offset: pos,
+                    version,
+                });
             }
         }
 
@@ -48,17 +500,28 @@ impl TrailerReader {
             let checksum_type = reader.read_u8()?;
             if checksum_type != 0x0 {
                 log::error!("Invalid checksum type");
-                return Err(crate::Error::UnsupportedChecksumType);
+                return Err(crate::Error::UnsupportedChecksumType {
+                    path: None,
+                    offset: pos,
+                    checksum_type,
+                });
             }
         }
 
         let toc_checksum = Checksum::from_raw(reader.read_u128::<LE>()?);
         let toc_pos = reader.read_u64::<LE>()?;
-        // let _toc_len = reader.read_u64::<LE>()?;
+        let toc_len = reader.read_u64::<LE>()?;
 
         Ok(ParsedTrailer {
+            pos,
+            version: 0x1,
             toc_checksum,
             toc_pos,
+            toc_len,
+            flags: 0,
+            merkle_root: None,
+            block_index_digest: None,
+            parity_digest: None,
         })
     }
 }
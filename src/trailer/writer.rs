@@ -2,30 +2,115 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::checksum::Checksum;
+use crate::checksum::{Checksum, MAX_DIGEST_LEN};
 use byteorder::WriteBytesExt;
+use std::io::Write;
 
 pub const TRAILER_MAGIC: &[u8] = b"SFA!";
 
+pub const TRAILER_VERSION: u8 = 0x2;
+
+/// Version of the trailer that additionally contains digests of the archive metadata
+/// (see [`TrailerDigests`])
+pub const TRAILER_VERSION_EXTENDED: u8 = 0x3;
+
+/// The table of contents is followed by a signature
+pub const FLAG_SIGNED: u8 = 0b0000_0001;
+
+/// Each section is followed by a section marker
+pub const FLAG_SECTION_MARKERS: u8 = 0b0000_0010;
+
+/// The table of contents is preceded by a mirrored copy of the table of contents and trailer
+pub const FLAG_TOC_MIRROR: u8 = 0b0000_0100;
+
+/// The (mirrored) table of contents is preceded by a block index,
+/// whose digest is stored in the (version 3) trailer
+pub const FLAG_BLOCK_CHECKSUMS: u8 = 0b0000_1000;
+
+/// The block index (or where it would be) is preceded by a parity region,
+/// whose digest is stored in the (version 3) trailer
+pub const FLAG_PARITY: u8 = 0b0001_0000;
+
+/// The (version 3) trailer contains a Merkle root over the sections
+pub const FLAG_MERKLE_ROOT: u8 = 0b0010_0000;
+
+/// The table of contents entries are followed by a bitmap of the entries that are aliases
+pub const FLAG_ALIASES: u8 = 0b0100_0000;
+
+/// If the table of contents is mirrored, the mirrored trailer is followed by zero padding,
+/// so the (primary) table of contents starts at a multiple of this, and no page of the file
+/// contains parts of both copies
+pub const MIRROR_ALIGNMENT: u64 = 4_096;
+
+/// Size of the signature that follows the table of contents, if signed
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Digests stored in a version 3 trailer, so they are covered by the trailer checksum
+/// and the signature
+///
+/// All digests take up space in a version 3 trailer (zero-padded), whether they are set or not.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TrailerDigests {
+    /// Merkle root over the sections
+    pub merkle_root: Option<Checksum>,
+
+    /// Digest of the block index
+    pub block_index: Option<Checksum>,
+
+    /// Digest of the parity region (without the parity shards, which have their own checksums)
+    pub parity: Option<Checksum>,
+}
+
+impl TrailerDigests {
+    fn is_empty(&self) -> bool {
+        self.merkle_root.is_none() && self.block_index.is_none() && self.parity.is_none()
+    }
+}
+
 pub struct TrailerWriter;
 
 impl TrailerWriter {
-    pub fn write_into<W: std::io::Write>(
+    pub fn write_into<W: Write>(
         mut writer: W,
         toc_checksum: Checksum,
         toc_pos: u64,
         toc_len: u64,
+        flags: u8,
+        digests: &TrailerDigests,
     ) -> crate::Result<()> {
         use byteorder::LE;
 
         log::trace!("Writing trailer");
 
-        writer.write_all(TRAILER_MAGIC)?;
-        writer.write_u8(0x1)?; // Version
-        writer.write_u8(0x0)?; // Checksum type, xxh3 = 0x0
-        writer.write_u128::<LE>(toc_checksum.into_u128())?;
-        writer.write_u64::<LE>(toc_pos)?;
-        writer.write_u64::<LE>(toc_len)?;
+        let mut buf = Vec::with_capacity(super::reader::TRAILER_V3_SIZE);
+        buf.write_all(toc_checksum.as_padded_bytes())?;
+        buf.write_u64::<LE>(toc_pos)?;
+        buf.write_u64::<LE>(toc_len)?;
+        buf.write_u8(flags)?;
+        buf.write_u8(toc_checksum.checksum_type().into())?;
+
+        let extended = !digests.is_empty();
+
+        if extended {
+            for digest in [digests.merkle_root, digests.block_index, digests.parity] {
+                match digest {
+                    Some(digest) => buf.write_all(digest.as_padded_bytes())?,
+                    None => buf.write_all(&[0; MAX_DIGEST_LEN])?,
+                }
+            }
+        }
+
+        let trailer_checksum = xxhash_rust::xxh3::xxh3_64(&buf);
+        buf.write_u64::<LE>(trailer_checksum)?;
+
+        buf.write_u8(if extended {
+            TRAILER_VERSION_EXTENDED
+        } else {
+            TRAILER_VERSION
+        })?;
+        buf.write_all(TRAILER_MAGIC)?;
+
+        writer.write_all(&buf)?;
 
         Ok(())
     }
@@ -0,0 +1,432 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Structural validation
+//!
+//! Checks the layout of an archive beyond its checksums: every byte of the file
+//! should belong to exactly one region (a section, section marker, parity region, block index,
+//! mirrored table of contents, table of contents, signature or trailer).
+//!
+//! Sections that are marked as aliases in the table of contents
+//! (see [`Writer::alias`](crate::Writer::alias) and [`Writer::use_dedup`](crate::Writer::use_dedup))
+//! are not reported as overlapping, but need to lie within another section.
+
+use crate::{
+    toc::{
+        entry::{SectionName, TocEntry},
+        reader::TocReader,
+    },
+    trailer::{
+        reader::{ParsedTrailer, TrailerReader},
+        writer::TRAILER_MAGIC,
+    },
+    ReaderOptions,
+};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+};
+
+/// Structural issue found by [`Reader::validate`](crate::Reader::validate)
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// Two sections overlap
+    ///
+    /// Aliases (see [`TocEntry::is_alias`](crate::TocEntry::is_alias)) are not reported.
+    OverlappingSections {
+        /// The section that starts first
+        first: SectionName,
+
+        /// The section that starts inside the first one
+        second: SectionName,
+    },
+
+    /// An alias does not lie within a section that is not an alias
+    InvalidAlias {
+        /// The section name
+        section: SectionName,
+    },
+
+    /// A section does not lie before the table of contents (or its mirror)
+    SectionOutOfBounds {
+        /// The section name
+        section: SectionName,
+
+        /// Position of the section
+        pos: u64,
+
+        /// Length of the section
+        len: u64,
+    },
+
+    /// Multiple sections have the same name
+    DuplicateSectionName {
+        /// The section name
+        section: SectionName,
+    },
+
+    /// The table of contents entries do not use all bytes of the
+    /// table of contents, as defined in the trailer
+    TocLengthMismatch {
+        /// Length of the table of contents as defined in the trailer
+        expected: u64,
+
+        /// Number of bytes actually used by the table of contents
+        got: u64,
+    },
+
+    /// The mirrored table of contents is missing or corrupted
+    InvalidTocMirror,
+
+    /// The block index is missing or corrupted
+    InvalidBlockIndex,
+
+    /// The parity region is missing or corrupted
+    ///
+    /// Only the layout and the checksums of the parity shards are checked, not the parity itself.
+    InvalidParity,
+
+    /// Bytes that do not belong to any section or archive metadata
+    UnreferencedBytes {
+        /// Position of the bytes
+        pos: u64,
+
+        /// Number of bytes
+        len: u64,
+    },
+
+    /// Bytes after the trailer
+    TrailingBytes {
+        /// Position of the bytes
+        pos: u64,
+
+        /// Number of bytes
+        len: u64,
+    },
+}
+
+/// Report returned by [`Reader::validate`](crate::Reader::validate)
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issues were found.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Lists all issues that were found.
+    #[must_use]
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+}
+
+/// Finds the trailer, allowing for garbage after it.
+fn find_trailer(file: &mut File) -> crate::Result<ParsedTrailer> {
+    let e = match TrailerReader::from_reader(file) {
+        Ok(trailer) => return Ok(trailer),
+        Err(e) => e,
+    };
+
+    log::debug!("No trailer at end of file, scanning for trailer magic");
+
+    file.seek(SeekFrom::Start(0))?;
+    let candidates =
+        crate::marker::find_candidates(&mut BufReader::new(&mut *file), TRAILER_MAGIC)?;
+
+    for candidate in candidates.into_iter().rev() {
+        if let Ok(trailer) =
+            TrailerReader::read_current(file, candidate + TRAILER_MAGIC.len() as u64)
+        {
+            return Ok(trailer);
+        }
+    }
+
+    Err(e)
+}
+
+/// Reports aliases that do not lie within a section that is not an alias.
+fn find_invalid_aliases(entries: &[TocEntry], issues: &mut Vec<ValidationIssue>) {
+    let mut sections = entries
+        .iter()
+        .filter(|entry| entry.len > 0 && !entry.alias)
+        .map(|entry| (entry.pos, entry.pos.saturating_add(entry.len)))
+        .collect::<Vec<_>>();
+    sections.sort_unstable();
+
+    for entry in entries.iter().filter(|entry| entry.len > 0 && entry.alias) {
+        let end = entry.pos.saturating_add(entry.len);
+
+        // NOTE: Overlapping sections are reported separately, so only the last section
+        // starting before (or with) the alias is considered
+        let idx = sections.partition_point(|(pos, _)| *pos <= entry.pos);
+        let contained = idx
+            .checked_sub(1)
+            .and_then(|idx| sections.get(idx))
+            .is_some_and(|(_, section_end)| end <= *section_end);
+
+        if !contained {
+            issues.push(ValidationIssue::InvalidAlias {
+                section: entry.name.clone(),
+            });
+        }
+    }
+}
+
+/// Reports sections that overlap, excluding aliases.
+fn find_overlaps(entries: &[TocEntry], issues: &mut Vec<ValidationIssue>) {
+    let mut sections = entries
+        .iter()
+        .filter(|entry| entry.len > 0 && !entry.alias)
+        .collect::<Vec<_>>();
+    sections.sort_by_key(|entry| entry.pos);
+
+    // The section that extends the furthest so far
+    let mut furthest: Option<(&TocEntry, u64)> = None;
+
+    for entry in sections {
+        let end = entry.pos.saturating_add(entry.len);
+
+        match furthest {
+            Some((first, first_end)) if entry.pos < first_end => {
+                issues.push(ValidationIssue::OverlappingSections {
+                    first: first.name.clone(),
+                    second: entry.name.clone(),
+                });
+
+                if end > first_end {
+                    furthest = Some((entry, end));
+                }
+            }
+            _ => furthest = Some((entry, end)),
+        }
+    }
+}
+
+/// Reports the gaps between the given `(start, end)` regions.
+fn find_unreferenced(mut regions: Vec<(u64, u64)>, issues: &mut Vec<ValidationIssue>) {
+    regions.sort_unstable();
+
+    let mut cursor = 0;
+
+    for (start, end) in regions {
+        if start > cursor {
+            issues.push(ValidationIssue::UnreferencedBytes {
+                pos: cursor,
+                len: start - cursor,
+            });
+        }
+        cursor = cursor.max(end);
+    }
+}
+
+/// Reads the block index, returning its start and end position.
+fn read_block_index(
+    file: &mut File,
+    trailer: &ParsedTrailer,
+    options: &ReaderOptions,
+) -> crate::Result<(u64, u64)> {
+    let (pos, _) = crate::block_index::read(file, trailer, options)?;
+
+    // NOTE: The block index was read successfully, so its end is known
+    let end = crate::block_index::end(file, trailer)?.unwrap_or(trailer.toc_pos);
+
+    Ok((pos, end))
+}
+
+/// Reads the parity region located before the block index at `block_index_pos` (if any),
+/// returning its start and end position.
+fn read_parity(
+    file: &mut File,
+    trailer: &ParsedTrailer,
+    block_index_pos: Option<u64>,
+) -> crate::Result<(u64, u64)> {
+    let Some(end) = crate::parity::end(file, trailer, block_index_pos)? else {
+        return Err(crate::Error::InvalidParity {
+            path: None,
+            offset: trailer.toc_pos,
+        });
+    };
+
+    let parity = crate::parity::read(file, end, trailer.parity_digest)?;
+
+    Ok((parity.pos, end))
+}
+
+/// Reads the block index and parity region (if any), adding them to `regions`.
+///
+/// Returns the position where the sections need to end.
+fn read_indexes(
+    file: &mut File,
+    trailer: &ParsedTrailer,
+    options: &ReaderOptions,
+    mut data_end: u64,
+    regions: &mut Vec<(u64, u64)>,
+    issues: &mut Vec<ValidationIssue>,
+) -> crate::Result<u64> {
+    let mut block_index_pos = None;
+
+    if trailer.has_block_checksums() {
+        match read_block_index(file, trailer, options) {
+            Ok((pos, end)) => {
+                regions.push((pos, end));
+                data_end = pos;
+                block_index_pos = Some(pos);
+            }
+            // NOTE: Exceeding a limit is not a structural issue
+            Err(e @ crate::Error::BlockIndexTooLarge { .. }) => return Err(e),
+            Err(e) => {
+                log::warn!("Block index is corrupted: {e:?}");
+                issues.push(ValidationIssue::InvalidBlockIndex);
+            }
+        }
+    }
+
+    if trailer.has_parity() {
+        match read_parity(file, trailer, block_index_pos) {
+            Ok((pos, end)) => {
+                regions.push((pos, end));
+                data_end = pos;
+            }
+            Err(e) => {
+                log::warn!("Parity region is corrupted: {e:?}");
+                issues.push(ValidationIssue::InvalidParity);
+            }
+        }
+    }
+
+    Ok(data_end)
+}
+
+/// Validates the structure of the archive.
+pub fn validate(file: &mut File, options: &ReaderOptions) -> crate::Result<ValidationReport> {
+    log::debug!("Validating archive structure");
+
+    let file_len = file.metadata()?.len();
+    let trailer = find_trailer(file)?;
+
+    let mut issues = vec![];
+
+    // NOTE: The ToC may not end where expected, but it must not overlap the trailer (or signature)
+    if trailer
+        .toc_pos
+        .checked_add(trailer.toc_len)
+        .is_none_or(|end| end > trailer.toc_end())
+    {
+        return Err(crate::Error::TocOutOfBounds {
+            path: None,
+            offset: trailer.toc_pos,
+            len: trailer.toc_len,
+            expected_end: trailer.toc_end(),
+        });
+    }
+
+    if trailer.toc_len > options.max_toc_size {
+        return Err(crate::Error::TocTooLarge {
+            path: None,
+            offset: trailer.toc_pos,
+            len: trailer.toc_len,
+            limit: options.max_toc_size,
+        });
+    }
+
+    // NOTE: The ToC is located before the trailer, so its length is bounded by the file size
+    #[allow(clippy::cast_possible_truncation)]
+    let mut toc_bytes = vec![0; trailer.toc_len as usize];
+    file.seek(SeekFrom::Start(trailer.toc_pos))?;
+    file.read_exact(&mut toc_bytes)?;
+
+    TocReader::verify_checksum(&toc_bytes, &trailer)?;
+    let (entries, toc_used) = TocReader::parse_lenient(&toc_bytes, &trailer, options)?;
+
+    if toc_used != toc_bytes.len() {
+        issues.push(ValidationIssue::TocLengthMismatch {
+            expected: trailer.toc_len,
+            got: toc_used as u64,
+        });
+    }
+
+    // Regions of the file that are referenced by the archive metadata
+    let mut regions = vec![
+        (trailer.toc_pos, trailer.toc_pos + toc_used as u64),
+        (trailer.toc_end(), trailer.pos),
+        (trailer.pos, trailer.pos + trailer.size()),
+    ];
+
+    let mut data_end = trailer.toc_pos;
+
+    if trailer.has_toc_mirror() {
+        let mirror = TrailerReader::read_mirror(file, &trailer).and_then(|mirror| {
+            let bytes = TocReader::read_bytes(file, &mirror, options)?;
+            TocReader::verify_checksum(&bytes, &mirror)?;
+            Ok(mirror)
+        });
+
+        match mirror {
+            Ok(mirror) => {
+                regions.push((mirror.toc_pos, mirror.toc_pos + mirror.toc_len));
+                // NOTE: The mirrored trailer is followed by zero padding up to the table of contents
+                regions.push((mirror.pos, trailer.toc_pos));
+                data_end = mirror.toc_pos;
+            }
+            Err(e) => {
+                log::warn!("Mirrored table of contents is corrupted: {e:?}");
+                issues.push(ValidationIssue::InvalidTocMirror);
+            }
+        }
+    }
+
+    let data_end = read_indexes(file, &trailer, options, data_end, &mut regions, &mut issues)?;
+
+    let mut names = HashSet::new();
+
+    for entry in &entries {
+        if !names.insert(entry.name()) {
+            issues.push(ValidationIssue::DuplicateSectionName {
+                section: entry.name.clone(),
+            });
+        }
+
+        let Some(end) = entry
+            .pos
+            .checked_add(entry.len)
+            .filter(|end| *end <= data_end)
+        else {
+            issues.push(ValidationIssue::SectionOutOfBounds {
+                section: entry.name.clone(),
+                pos: entry.pos,
+                len: entry.len,
+            });
+            continue;
+        };
+
+        regions.push((entry.pos, end));
+
+        // NOTE: Aliases are not followed by a marker
+        if trailer.has_section_markers() && !entry.alias {
+            regions.push((end, end + crate::marker::size(entry)));
+        }
+    }
+
+    find_overlaps(&entries, &mut issues);
+    find_invalid_aliases(&entries, &mut issues);
+    find_unreferenced(regions, &mut issues);
+
+    let trailer_end = trailer.pos + trailer.size();
+
+    if file_len > trailer_end {
+        issues.push(ValidationIssue::TrailingBytes {
+            pos: trailer_end,
+            len: file_len - trailer_end,
+        });
+    }
+
+    Ok(ValidationReport { issues })
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    block_index::BlockChecksums, checksum::Hasher, toc::entry::SectionName, ChecksumType,
+    RandomAccessSource, TocEntry,
+};
+use std::{
+    io::{Read, Seek, SeekFrom},
+    sync::Arc,
+};
+
+/// Reader over a section that verifies every block it reads against the section's
+/// block checksums (see [`Writer::use_block_checksums`](crate::Writer::use_block_checksums))
+///
+/// Only the blocks that are touched by a read are read and verified, so the section can be read
+/// with random access, either using [`Read`] and [`Seek`] (which keep the last block read in memory),
+/// or using [`RandomAccessSource::read_at`] with offsets relative to the start of the section.
+///
+/// If a block is corrupted, the read fails with [`std::io::ErrorKind::InvalidData`], wrapping
+/// an [`Error::BlockChecksumMismatch`](crate::Error::BlockChecksumMismatch) that names the block.
+///
+/// Created using [`Reader::verified_reader`](crate::Reader::verified_reader).
+pub struct VerifiedReader<S: RandomAccessSource> {
+    source: S,
+    name: SectionName,
+    start: u64,
+    len: u64,
+    checksum_type: ChecksumType,
+    checksums: Arc<BlockChecksums>,
+
+    /// Current position, relative to `start`
+    pos: u64,
+
+    /// Index and contents of the last block read
+    block: Option<(u64, Vec<u8>)>,
+}
+
+impl<S: RandomAccessSource> VerifiedReader<S> {
+    pub(crate) fn new(
+        source: S,
+        entry: &TocEntry,
+        checksum_type: ChecksumType,
+        checksums: Arc<BlockChecksums>,
+    ) -> Self {
+        Self {
+            source,
+            name: entry.name.clone(),
+            start: entry.pos,
+            len: entry.len,
+            checksum_type,
+            checksums,
+            pos: 0,
+            block: None,
+        }
+    }
+
+    /// Returns the size of the blocks the section is verified in.
+    #[must_use]
+    pub fn block_size(&self) -> u32 {
+        self.checksums.block_size
+    }
+
+    /// Reads block `idx` into `buf`, and verifies it against its checksum.
+    fn read_block(&self, idx: u64, buf: &mut Vec<u8>) -> std::io::Result<()> {
+        let block_size = u64::from(self.checksums.block_size);
+        let offset = idx * block_size;
+
+        let Some(expected) = usize::try_from(idx)
+            .ok()
+            .and_then(|idx| self.checksums.get(idx))
+        else {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        };
+
+        // NOTE: Blocks are at most `u32::MAX` bytes long
+        #[allow(clippy::cast_possible_truncation)]
+        buf.resize(block_size.min(self.len.saturating_sub(offset)) as usize, 0);
+
+        self.source.read_at(self.start + offset, buf)?;
+
+        let mut hasher = Hasher::new(self.checksum_type);
+        hasher.update(buf);
+        let got = hasher.checksum();
+
+        if got != expected {
+            log::error!(
+                "Block {idx} of section {:?} is corrupted: expected {expected}, got {got}",
+                self.name,
+            );
+
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                crate::Error::BlockChecksumMismatch {
+                    path: None,
+                    section: self.name.clone(),
+                    block: idx,
+                    offset: self.start + offset,
+                    got: Box::new(got),
+                    expected: Box::new(expected),
+                },
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: RandomAccessSource> RandomAccessSource for VerifiedReader<S> {
+    fn size(&self) -> std::io::Result<u64> {
+        Ok(self.len)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        if offset
+            .checked_add(buf.len() as u64)
+            .is_none_or(|end| end > self.len)
+        {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+
+        let block_size = u64::from(self.checksums.block_size);
+        let mut block = vec![];
+        let mut buf = buf;
+        let mut offset = offset;
+
+        while !buf.is_empty() {
+            let idx = offset / block_size;
+            self.read_block(idx, &mut block)?;
+
+            #[allow(clippy::cast_possible_truncation)]
+            let available = block
+                .get((offset - idx * block_size) as usize..)
+                .unwrap_or_default();
+
+            let n = available.len().min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            {
+                buf[..n].copy_from_slice(&available[..n]);
+                buf = &mut buf[n..];
+            }
+
+            offset += n as u64;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: RandomAccessSource> Read for VerifiedReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_size = u64::from(self.checksums.block_size);
+        let idx = self.pos / block_size;
+
+        let block = match self.block.take() {
+            Some((cached, block)) if cached == idx => block,
+            cached => {
+                let mut block = cached.map(|(_, block)| block).unwrap_or_default();
+                self.read_block(idx, &mut block)?;
+                block
+            }
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        let available = block
+            .get((self.pos - idx * block_size) as usize..)
+            .unwrap_or_default();
+
+        let n = available.len().min(buf.len());
+
+        #[allow(clippy::indexing_slicing)]
+        buf[..n].copy_from_slice(&available[..n]);
+
+        self.pos += n as u64;
+        self.block = Some((idx, block));
+
+        Ok(n)
+    }
+}
+
+impl<S: RandomAccessSource> Seek for VerifiedReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(pos) = pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        self.pos = pos;
+
+        Ok(pos)
+    }
+}
@@ -2,28 +2,121 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+#[cfg(feature = "signing")]
+use crate::SigningKey;
 use crate::{
+    atomic::AtomicTarget,
+    block_index::{BlockChecksums, BlockHasher},
+    checksum::Hasher,
     checksum_writer::ChecksummedWriter,
+    file_copy,
+    parity::{DataStream, ParityLayout},
     toc::{
         entry::{SectionName, TocEntry},
         writer::TocWriter,
     },
-    trailer::writer::TrailerWriter,
-    Checksum,
+    trailer::writer::{
+        TrailerDigests, TrailerWriter, FLAG_ALIASES, FLAG_BLOCK_CHECKSUMS, FLAG_MERKLE_ROOT,
+        FLAG_PARITY, FLAG_SECTION_MARKERS, FLAG_SIGNED, FLAG_TOC_MIRROR, MIRROR_ALIGNMENT,
+    },
+    Checksum, ChecksumType, Durability, FinishedArchive, OverwritePolicy, RandomAccessSource,
+    SectionWriter, Toc,
 };
+
 use std::{
+    collections::HashMap,
     fs::File,
-    io::{BufWriter, Seek, Write},
-    path::PathBuf,
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
 };
 
+/// Size of the chunks that are hashed when copying a file into a section (or reading an alias range)
+const COPY_CHUNK_SIZE: usize = 256 * 1_024;
+
+/// Returns `true` if the `len` bytes at positions `a` and `b` of the file are identical.
+fn same_bytes(file: &File, a: u64, b: u64, len: u64) -> std::io::Result<bool> {
+    let chunk_size = usize::try_from(len)
+        .unwrap_or(usize::MAX)
+        .min(COPY_CHUNK_SIZE);
+
+    let mut buf_a = vec![0; chunk_size];
+    let mut buf_b = vec![0; chunk_size];
+    let mut offset = 0;
+
+    while offset < len {
+        let n = usize::try_from(len - offset)
+            .unwrap_or(usize::MAX)
+            .min(chunk_size);
+
+        #[allow(clippy::indexing_slicing)]
+        let (chunk_a, chunk_b) = (&mut buf_a[..n], &mut buf_b[..n]);
+
+        file.read_at(a + offset, chunk_a)?;
+        file.read_at(b + offset, chunk_b)?;
+
+        if chunk_a != chunk_b {
+            return Ok(false);
+        }
+
+        offset += n as u64;
+    }
+
+    Ok(true)
+}
+
+/// What happens to the file if a [`Writer`] is dropped without being finished
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DropBehavior {
+    /// Keep the incomplete file, e.g. to salvage its sections later
+    Keep,
+
+    /// Remove the incomplete file
+    Delete,
+
+    /// Keep the incomplete file and log a warning
+    #[default]
+    Log,
+}
+
 /// Archive writer
-#[allow(clippy::struct_field_names)]
+///
+/// If the writer is dropped without being finished, the incomplete file
+/// is handled according to its [`DropBehavior`].
+#[allow(clippy::struct_field_names, clippy::struct_excessive_bools)]
 pub struct Writer {
     writer: ChecksummedWriter<BufWriter<File>>,
     last_section_pos: u64,
     section_name: SectionName,
+    section_started: bool,
+    section_hasher: Hasher,
+
+    /// Stored checksum of the section being copied, which is used instead of hashing its bytes
+    /// (see [`Writer::copy_section_from`])
+    section_checksum: Option<Checksum>,
+
     toc: Vec<TocEntry>,
+    checksum_type: ChecksumType,
+    section_markers: bool,
+    toc_mirror: bool,
+    merkle_root: bool,
+
+    /// Positions of the sections written so far, by length and checksum (if deduplication is enabled)
+    dedup: Option<HashMap<(u64, Checksum), u64>>,
+
+    /// Block checksums of the current section (if block checksums are enabled)
+    block_hasher: Option<BlockHasher>,
+
+    /// Block checksums of the sections written so far, by position and length
+    block_index: Vec<((u64, u64), BlockChecksums)>,
+
+    /// Layout of the parity region (if parity is enabled)
+    parity: Option<ParityLayout>,
+
+    atomic: Option<AtomicTarget>,
+    durability: Durability,
+    path: Option<PathBuf>,
+    drop_behavior: DropBehavior,
+    finished: bool,
 }
 
 impl Writer {
@@ -34,11 +127,221 @@ impl Writer {
     /// Returns error, if an IO error occurred.
     pub fn new_at_path(path: impl Into<PathBuf>) -> crate::Result<Self> {
         let path = std::path::absolute(path.into())?;
-        let file = File::create_new(&path)?;
-        Ok(Self::from_writer(BufWriter::new(file)))
+
+        // NOTE: The file is read back to calculate the checksums of alias ranges
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| crate::Error::io_at(e, &path))?;
+
+        let mut writer = Self::from_writer(BufWriter::new(file));
+        writer.path = Some(path);
+        Ok(writer)
+    }
+
+    /// Creates a new writer that atomically creates the archive at `path`.
+    ///
+    /// The archive is written to a temporary file in the same directory,
+    /// which is synced and moved to `path` in [`Writer::finish`], followed by syncing the directory.
+    /// If the writer is not finished, `path` is never created, and the temporary file
+    /// is removed when the writer is dropped (see [`Writer::use_drop_behavior`]).
+    ///
+    /// By default, finishing fails if `path` already exists, see [`Writer::use_overwrite_policy`].
+    /// If the temporary file cannot be moved for any other reason, it is kept (and logged),
+    /// so the archive is not lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn new_atomic(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = std::path::absolute(path.into())?;
+        let (target, file) = AtomicTarget::create(path)?;
+
+        let mut writer = Self::from_writer(BufWriter::new(file));
+        writer.atomic = Some(target);
+
+        // NOTE: The temporary file is useless without the destination, so it is not left behind
+        writer.drop_behavior = DropBehavior::Delete;
+
+        Ok(writer)
+    }
+
+    /// Sets what happens if the destination of an atomic writer already exists
+    /// when the writer is finished.
+    ///
+    /// Defaults to [`OverwritePolicy::Fail`].
+    ///
+    /// Only applies to writers created with [`Writer::new_atomic`].
+    #[must_use]
+    pub fn use_overwrite_policy(mut self, policy: OverwritePolicy) -> Self {
+        if let Some(target) = &mut self.atomic {
+            target.overwrite_policy = policy;
+        }
+        self
+    }
+
+    /// Sets the checksum algorithm used for the table of contents and full-file checksums.
+    ///
+    /// Defaults to [`ChecksumType::Xxh3`].
+    ///
+    /// Needs to be set before any data is written.
+    #[must_use]
+    pub fn use_checksum_type(mut self, checksum_type: ChecksumType) -> Self {
+        self.checksum_type = checksum_type;
+        self.writer.set_checksum_type(checksum_type);
+        self.section_hasher = Hasher::new(checksum_type);
+        self.block_hasher = self
+            .block_hasher
+            .as_ref()
+            .map(|hasher| BlockHasher::new(checksum_type, hasher.block_size()));
+        self
+    }
+
+    /// Writes a marker after each section, so the sections can be recovered using
+    /// [`Reader::salvage`](crate::Reader::salvage) if the file is not finished or its tail is corrupted.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn use_section_markers(mut self, enabled: bool) -> Self {
+        self.section_markers = enabled;
+        self
+    }
+
+    /// Writes a second copy of the table of contents and trailer before the table of contents,
+    /// which is used by the [`Reader`](crate::Reader) if the primary table of contents
+    /// or trailer is corrupted.
+    ///
+    /// The copy is padded with zeros, so the primary table of contents starts on a separate
+    /// 4 KiB page, which adds up to 4 KiB to the archive.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn use_toc_mirror(mut self, enabled: bool) -> Self {
+        self.toc_mirror = enabled;
+        self
+    }
+
+    /// Stores the root of a Merkle tree over the sections in the trailer, so a single section can be
+    /// proven to be part of the archive (see [`Reader::inclusion_proof`](crate::Reader::inclusion_proof)
+    /// and [`verify_proof`](crate::verify_proof)) without having the rest of the archive.
+    ///
+    /// The Merkle root uses the archive's [`ChecksumType`], so use a cryptographic checksum type
+    /// to protect it against deliberate tampering. If the archive is signed, the signature covers
+    /// the Merkle root.
+    ///
+    /// This requires a version 3 trailer, which older readers do not support.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn use_merkle_root(mut self, enabled: bool) -> Self {
+        self.merkle_root = enabled;
+        self
+    }
+
+    /// Stores identical sections only once.
+    ///
+    /// When a section is closed, and a section with the same length and checksum was already
+    /// written, the section is removed from the end of the file again, and its table of contents
+    /// entry points at the existing section instead. Readers do not need to know about this.
+    ///
+    /// Sections are matched by their checksums, and their bytes are compared
+    /// (by reading them back) before a section is removed.
+    /// Deduplicated sections are not followed by a section marker,
+    /// so they are not recovered by [`Reader::salvage`](crate::Reader::salvage).
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn use_dedup(mut self, enabled: bool) -> Self {
+        self.dedup = enabled.then(HashMap::new);
+        self
+    }
+
+    /// Stores a checksum for every `block_size` bytes of each section in a block index,
+    /// so sections can be read with random access while only verifying the blocks that are read
+    /// (see [`Reader::verified_reader`](crate::Reader::verified_reader)).
+    ///
+    /// The blocks use the archive's [`ChecksumType`]. Sections that share all bytes of
+    /// another section (see [`Writer::alias`] and [`Writer::use_dedup`]) share its block checksums,
+    /// other aliases have none.
+    ///
+    /// A block size of `0` disables block checksums, which is the default.
+    ///
+    /// Needs to be set before any data is written.
+    #[must_use]
+    pub fn use_block_checksums(mut self, block_size: u32) -> Self {
+        self.block_hasher =
+            (block_size > 0).then(|| BlockHasher::new(self.checksum_type, block_size));
+        self
+    }
+
+    /// Stores Reed-Solomon parity over the section data in a parity region, so damaged sections
+    /// can be repaired in place using [`Reader::repair`](crate::Reader::repair).
+    ///
+    /// The bytes of all sections are split into shards of `shard_size` bytes, and every
+    /// `data_shards` consecutive shards get `parity_shards` parity shards, which are calculated
+    /// when the writer is finished. Up to `parity_shards` damaged shards of each group can be
+    /// reconstructed, so the parity takes `parity_shards / data_shards` of the section data's size.
+    ///
+    /// Damaged bytes are located using the section checksums, or the block checksums
+    /// (see [`Writer::use_block_checksums`]) if enabled, and all shards overlapping a damaged
+    /// section or block are reconstructed. So without block checksums, only sections spanning
+    /// a few shards can be repaired; use a block size of at most `shard_size` to repair
+    /// damage anywhere.
+    ///
+    /// The file is read back to calculate the parity, so writers created with
+    /// [`Writer::from_writer`] need a readable file.
+    ///
+    /// All values need to be greater than `0`, and `data_shards + parity_shards` may not
+    /// exceed 256, otherwise [`Writer::finish`] fails with
+    /// [`Error::InvalidParityLayout`](crate::Error::InvalidParityLayout).
+    /// Parity is disabled by default.
+    #[must_use]
+    pub fn use_parity(mut self, shard_size: u32, data_shards: u8, parity_shards: u8) -> Self {
+        self.parity = Some(ParityLayout {
+            shard_size,
+            data_shards,
+            parity_shards,
+        });
+        self
+    }
+
+    /// Sets how the archive is persisted when the writer is finished.
+    ///
+    /// Defaults to [`Durability::SyncAll`].
+    #[must_use]
+    pub fn use_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Sets what happens to the file if the writer is dropped without being finished.
+    ///
+    /// Defaults to [`DropBehavior::Log`], or [`DropBehavior::Delete`] for writers created
+    /// with [`Writer::new_atomic`].
+    ///
+    /// For atomic writers, this applies to the temporary file; the destination is never created.
+    /// Writers created with [`Writer::from_writer`] do not know the path of their file,
+    /// so the file is always kept.
+    #[must_use]
+    pub fn use_drop_behavior(mut self, drop_behavior: DropBehavior) -> Self {
+        self.drop_behavior = drop_behavior;
+        self
+    }
+
+    /// Returns the path of the file being written, if known.
+    pub(crate) fn file_path(&self) -> Option<&Path> {
+        self.atomic
+            .as_ref()
+            .map(|target| target.tmp_path.as_path())
+            .or(self.path.as_deref())
     }
 
     /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Data written to it bypasses the checksums, and (if written outside of a section)
+    /// becomes an unnamed section. Use [`Writer::section`] to write sections instead.
     pub fn get_mut(&mut self) -> impl Write + Seek + '_ {
         self.writer.inner()
     }
@@ -47,87 +350,848 @@ impl Writer {
     #[must_use]
     pub fn from_writer(writer: BufWriter<File>) -> Self {
         Self {
-            writer: ChecksummedWriter::new(writer),
+            writer: ChecksummedWriter::new(writer, ChecksumType::default()),
             last_section_pos: 0,
             section_name: SectionName::new(),
+            section_started: false,
+            section_hasher: Hasher::new(ChecksumType::default()),
+            section_checksum: None,
             toc: Vec::new(),
+            checksum_type: ChecksumType::default(),
+            section_markers: false,
+            toc_mirror: false,
+            merkle_root: false,
+            dedup: None,
+            block_hasher: None,
+            block_index: Vec::new(),
+            parity: None,
+            atomic: None,
+            durability: Durability::default(),
+            path: None,
+            drop_behavior: DropBehavior::default(),
+            finished: false,
         }
     }
 }
 
-impl std::io::Write for Writer {
-    fn flush(&mut self) -> std::io::Result<()> {
+impl Writer {
+    /// Starts a named section, closing the current section (if any).
+    fn start(&mut self, name: impl Into<SectionName>) -> std::io::Result<()> {
+        self.append_toc_entry()?;
+        self.section_name = name.into();
+        self.section_started = true;
+        Ok(())
+    }
+
+    /// Writes data into the current section, see [`SectionWriter`].
+    pub(crate) fn write_section_data(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.writer.write(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.hash_section(&buf[..n]);
+
+        Ok(n)
+    }
+
+    /// Flushes the buffered data of the current section, see [`SectionWriter`].
+    pub(crate) fn flush_section_data(&mut self) -> std::io::Result<()> {
         self.writer.flush()
     }
 
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.writer.write(buf)
+    /// Starts a named section, which is closed when the returned [`SectionWriter`] is
+    /// finished or dropped.
+    ///
+    /// The [`SectionWriter`] is the only way to write data into the archive,
+    /// so all data belongs to a named section.
+    ///
+    /// ```
+    /// use sfa::Writer;
+    /// use std::io::Write;
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("hello.sfa");
+    ///
+    /// let mut writer = Writer::new_at_path(&path)?;
+    ///
+    /// let mut section = writer.section("Section 1")?;
+    /// section.write_all(b"Hello world!\n")?;
+    /// let entry = section.finish()?;
+    /// assert_eq!(13, entry.len());
+    ///
+    /// {
+    ///     let mut section = writer.section("Section 2")?;
+    ///     section.write_all(b"Hello again!\n")?;
+    ///     // Section is closed when dropped
+    /// }
+    ///
+    /// writer.finish()?;
+    /// #
+    /// # Ok::<(), sfa::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn section(&mut self, name: impl Into<SectionName>) -> std::io::Result<SectionWriter<'_>> {
+        self.start(name)?;
+        Ok(SectionWriter::new(self))
     }
-}
 
-impl Writer {
-    /// Starts the first named section.
+    /// Appends a section with the contents of `file`, from its start to its end.
+    ///
+    /// On Linux, the data is copied inside the kernel using `copy_file_range`
+    /// (which may use reflinks or server-side copies, depending on the file system),
+    /// instead of being written through the [`Writer`]'s buffer.
+    /// The copied data is then read back from the archive to calculate the section
+    /// and full-file checksums, so the source is only read once.
+    /// If the kernel cannot copy between the files, or on other platforms, the data is
+    /// copied through userspace.
+    ///
+    /// Returns the table of contents entry of the section.
     ///
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub fn start(&mut self, name: impl Into<SectionName>) -> std::io::Result<()> {
+    pub fn append_section_from_file(
+        &mut self,
+        name: impl Into<SectionName>,
+        file: &File,
+    ) -> std::io::Result<TocEntry> {
+        self.start(name)?;
+        self.copy_from_file(file, 0, file.metadata()?.len())?;
+        self.finish_section()
+    }
+
+    /// Appends a section with the next `len` bytes of `reader`.
+    ///
+    /// Returns the table of contents entry of the section.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the reader ended
+    /// before `len` bytes were read ([`std::io::ErrorKind::UnexpectedEof`]).
+    pub fn append_section_from_reader(
+        &mut self,
+        name: impl Into<SectionName>,
+        reader: impl Read,
+        len: u64,
+    ) -> std::io::Result<TocEntry> {
+        let mut section = self.section(name)?;
+
+        let copied = std::io::copy(&mut reader.take(len), &mut section)?;
+        if copied < len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("section reader ended after {copied} of {len} bytes"),
+            ));
+        }
+
+        section.finish()
+    }
+
+    /// Adds a section `name` that shares the bytes of the last section named `existing`
+    /// (an alias), without writing them again.
+    ///
+    /// The current section is closed first, so it can be aliased as well.
+    ///
+    /// Returns the table of contents entry of the alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or no section named `existing` was closed yet
+    /// ([`std::io::ErrorKind::InvalidInput`]).
+    pub fn alias(
+        &mut self,
+        name: impl Into<SectionName>,
+        existing: impl AsRef<[u8]>,
+    ) -> std::io::Result<TocEntry> {
         self.append_toc_entry()?;
-        self.section_name = name.into();
+
+        let mut entry = self.find_section(existing.as_ref())?.clone();
+        entry.name = name.into();
+        entry.alias = true;
+
+        self.toc.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    /// Adds a section `name` that shares `len` bytes (starting at `offset`) of the
+    /// last section named `existing` (an alias), without writing them again.
+    ///
+    /// The current section is closed first, so it can be aliased as well.
+    ///
+    /// The checksum of the range is calculated by reading it back from the file,
+    /// so writers created with [`Writer::from_writer`] need their file to be opened for reading.
+    ///
+    /// Returns the table of contents entry of the alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, no section named `existing` was closed yet,
+    /// or the range exceeds the section ([`std::io::ErrorKind::InvalidInput`]).
+    pub fn alias_range(
+        &mut self,
+        name: impl Into<SectionName>,
+        existing: impl AsRef<[u8]>,
+        offset: u64,
+        len: u64,
+    ) -> std::io::Result<TocEntry> {
+        self.append_toc_entry()?;
+
+        let section = self.find_section(existing.as_ref())?;
+
+        if offset.checked_add(len).is_none_or(|end| end > section.len) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "alias range {offset}..+{len} exceeds section of {} bytes",
+                    section.len,
+                ),
+            ));
+        }
+
+        let pos = section.pos + offset;
+
+        self.alias_at(name, pos, len)
+    }
+
+    /// Adds a section `name` that shares `len` bytes of the file (starting at `pos`),
+    /// which need to lie within a closed section.
+    ///
+    /// The checksum of the range is calculated by reading it back from the file,
+    /// unless the range is exactly a closed section.
+    pub(crate) fn alias_at(
+        &mut self,
+        name: impl Into<SectionName>,
+        pos: u64,
+        len: u64,
+    ) -> std::io::Result<TocEntry> {
+        self.append_toc_entry()?;
+
+        let checksum = match self
+            .toc
+            .iter()
+            .filter(|entry| entry.pos == pos && entry.len == len)
+            .find_map(|entry| entry.checksum)
+        {
+            Some(checksum) => checksum,
+            None => self.checksum_range(pos, len)?,
+        };
+
+        let entry = TocEntry {
+            name: name.into(),
+            pos,
+            len,
+            checksum: Some(checksum),
+            alias: true,
+        };
+
+        self.toc.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    /// Calculates the checksum of `len` bytes of the file (starting at `pos`)
+    /// by reading them back.
+    fn checksum_range(&mut self, pos: u64, len: u64) -> std::io::Result<Checksum> {
+        // NOTE: Buffered data needs to be written before reading it back
+        self.writer.flush()?;
+
+        let file = self.writer.inner().get_ref();
+        let mut hasher = Hasher::new(self.checksum_type);
+        let mut buf = vec![
+            0;
+            usize::try_from(len)
+                .unwrap_or(usize::MAX)
+                .min(COPY_CHUNK_SIZE)
+        ];
+        let mut read = 0;
+
+        while read < len {
+            let n = usize::try_from(len - read)
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            let chunk = &mut buf[..n];
+
+            file.read_at(pos + read, chunk)?;
+            hasher.update(chunk);
+
+            read += n as u64;
+        }
+
+        Ok(hasher.checksum())
+    }
+
+    /// Returns the last closed section with the given name.
+    fn find_section(&self, name: &[u8]) -> std::io::Result<&TocEntry> {
+        self.toc
+            .iter()
+            .rev()
+            .find(|entry| entry.name() == name)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("section {:?} does not exist", String::from_utf8_lossy(name)),
+                )
+            })
+    }
+
+    /// Appends a copy of a section of the archive at `path`, as listed in its table of contents
+    /// (see [`Reader::toc`](crate::Reader::toc)).
+    ///
+    /// This takes the path of the archive instead of its [`Reader`](crate::Reader),
+    /// because readers do not keep the archive open; `entry` can be taken
+    /// from any reader of the archive at `path`.
+    ///
+    /// The stored bytes are copied as they are (inside the kernel on Linux, see
+    /// [`Writer::append_section_from_file`]), and the section keeps its name.
+    /// If both archives use the same [`ChecksumType`], the stored section checksum is
+    /// copied as well, instead of being calculated again. So a corrupted section is still
+    /// detected when reading the new archive (e.g. using [`Reader::verify`](crate::Reader::verify)).
+    ///
+    /// Returns the table of contents entry of the new section.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    /// In that case, the archive being written should be aborted.
+    pub fn copy_section_from(
+        &mut self,
+        path: impl AsRef<Path>,
+        entry: &TocEntry,
+    ) -> crate::Result<TocEntry> {
+        self.copy_section_as(path, entry, entry.name())
+    }
+
+    /// Appends a copy of a section of the archive at `path` under a new name.
+    pub(crate) fn copy_section_as(
+        &mut self,
+        path: impl AsRef<Path>,
+        entry: &TocEntry,
+        name: impl Into<SectionName>,
+    ) -> crate::Result<TocEntry> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| crate::Error::io_at(e, path))?;
+
+        self.start(name)?;
+
+        self.section_checksum = entry
+            .checksum()
+            .filter(|checksum| checksum.checksum_type() == self.checksum_type);
+
+        self.copy_from_file(&file, entry.pos(), entry.len())?;
+
+        Ok(self.finish_section()?)
+    }
+
+    /// Copies `len` bytes of `file` (starting at `offset`) into the current section.
+    fn copy_from_file(&mut self, file: &File, offset: u64, len: u64) -> std::io::Result<()> {
+        // NOTE: Buffered data needs to be written before writing to the file directly
+        self.writer.flush()?;
+
+        let mut buf = vec![
+            0;
+            usize::try_from(len)
+                .unwrap_or(usize::MAX)
+                .min(COPY_CHUNK_SIZE)
+        ];
+        let mut in_kernel = true;
+        let mut copied = 0;
+        let dst_pos = self.writer.inner().stream_position()?;
+
+        while copied < len {
+            let n = usize::try_from(len - copied)
+                .unwrap_or(usize::MAX)
+                .min(buf.len());
+
+            #[allow(clippy::indexing_slicing)]
+            let chunk = &mut buf[..n];
+
+            if in_kernel {
+                in_kernel = self.copy_chunk_in_kernel(file, offset + copied, n)?;
+            }
+
+            if in_kernel {
+                // NOTE: The copied data is read back from the archive for hashing,
+                // so the source is only read once
+                self.writer
+                    .inner()
+                    .get_ref()
+                    .read_at(dst_pos + copied, chunk)?;
+                self.writer.update(chunk);
+            } else {
+                file.read_at(offset + copied, chunk)?;
+                self.writer.write_all(chunk)?;
+            }
+
+            self.hash_section(chunk);
+
+            copied += n as u64;
+        }
+
         Ok(())
     }
 
-    fn append_toc_entry(&mut self) -> std::io::Result<()> {
+    /// Copies `len` bytes of `file` (starting at `offset`) to the end of the archive inside the kernel.
+    ///
+    /// Returns `false` if the kernel cannot copy between the files, in which case nothing was copied.
+    fn copy_chunk_in_kernel(
+        &mut self,
+        file: &File,
+        offset: u64,
+        len: usize,
+    ) -> std::io::Result<bool> {
+        let dst = self.writer.inner().get_ref();
+        let mut copied = 0;
+
+        while copied < len {
+            match file_copy::copy_file_range(file, offset + copied as u64, dst, len - copied) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)),
+                Ok(n) => copied += n,
+                Err(e) if copied == 0 && file_copy::is_unsupported(&e) => {
+                    log::debug!(
+                        "Cannot copy file inside the kernel, falling back to userspace: {e:?}"
+                    );
+                    return Ok(false);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Updates the checksums of the current section with data that was written to the file.
+    fn hash_section(&mut self, buf: &[u8]) {
+        if self.section_checksum.is_none() {
+            self.section_hasher.update(buf);
+        }
+
+        if let Some(hasher) = &mut self.block_hasher {
+            hasher.update(buf);
+        }
+    }
+
+    /// Closes the current section, which needs to have been started, returning its table of contents entry.
+    pub(crate) fn finish_section(&mut self) -> std::io::Result<TocEntry> {
+        #[allow(clippy::expect_used)]
+        Ok(self
+            .close_section()?
+            .expect("section should be started before it is finished"))
+    }
+
+    /// Closes the current section, returning its table of contents entry.
+    pub(crate) fn close_section(&mut self) -> std::io::Result<Option<TocEntry>> {
+        Ok(self
+            .append_toc_entry()?
+            .then(|| self.toc.last().cloned())
+            .flatten())
+    }
+
+    /// Appends the current section to the table of contents, if any.
+    ///
+    /// Returns `true` if a table of contents entry was appended.
+    fn append_toc_entry(&mut self) -> std::io::Result<bool> {
         let file_pos = self.writer.inner().stream_position()?;
 
-        if file_pos > 0 {
+        // NOTE: Data written to the underlying writer outside of a section (see `Writer::get_mut`)
+        // becomes an unnamed section
+        let appended = self.section_started || file_pos > self.last_section_pos;
+
+        if appended {
             let name = std::mem::take(&mut self.section_name);
-            self.toc.push(TocEntry {
+            let hasher =
+                std::mem::replace(&mut self.section_hasher, Hasher::new(self.checksum_type));
+
+            let mut entry = TocEntry {
                 name,
                 pos: self.last_section_pos,
                 len: file_pos - self.last_section_pos,
-            });
+                checksum: Some(
+                    self.section_checksum
+                        .take()
+                        .unwrap_or_else(|| hasher.checksum()),
+                ),
+                alias: false,
+            };
+
+            let deduplicated = self.deduplicate(&mut entry)?;
+
+            // NOTE: Deduplicated sections share the block checksums of the existing section
+            if let Some(hasher) = &mut self.block_hasher {
+                let checksums = hasher.finish();
+
+                if !deduplicated && entry.len > 0 {
+                    self.block_index.push(((entry.pos, entry.len), checksums));
+                }
+            }
+
+            if self.section_markers && !deduplicated {
+                crate::marker::write_into(&mut self.writer, &entry)?;
+            }
+
+            self.toc.push(entry);
+            self.section_started = false;
         }
 
-        self.last_section_pos = file_pos;
+        self.last_section_pos = self.writer.inner().stream_position()?;
 
-        Ok(())
+        if self.dedup.is_some() {
+            self.writer.set_checkpoint();
+        }
+
+        Ok(appended)
+    }
+
+    /// Points the entry of the section that was just written at an identical section written before,
+    /// if there is one, and removes the section from the file.
+    ///
+    /// Returns `true` if the section was deduplicated.
+    fn deduplicate(&mut self, entry: &mut TocEntry) -> std::io::Result<bool> {
+        let Some(dedup) = &mut self.dedup else {
+            return Ok(false);
+        };
+
+        let Some(checksum) = entry.checksum else {
+            return Ok(false);
+        };
+
+        if entry.len == 0 {
+            return Ok(false);
+        }
+
+        let Some(&pos) = dedup.get(&(entry.len, checksum)) else {
+            dedup.insert((entry.len, checksum), entry.pos);
+            return Ok(false);
+        };
+
+        // NOTE: The checksums may collide, so compare the actual bytes before removing the section
+        let file = self.writer.inner();
+        file.flush()?;
+
+        if !same_bytes(file.get_ref(), pos, entry.pos, entry.len)? {
+            log::debug!(
+                "Section {:?} at {} has the same checksum as the section at {pos}, but different bytes",
+                entry.name,
+                entry.pos,
+            );
+            return Ok(false);
+        }
+
+        // NOTE: The full-file checksum needs to be restored to before the section was written
+        if !self.writer.rollback() {
+            return Ok(false);
+        }
+
+        log::trace!(
+            "Deduplicating section {:?} at {}, which is identical to the section at {pos}",
+            entry.name,
+            entry.pos,
+        );
+
+        let file = self.writer.inner();
+        file.get_ref().set_len(entry.pos)?;
+        file.seek(SeekFrom::Start(entry.pos))?;
+
+        entry.pos = pos;
+        entry.alias = true;
+
+        Ok(true)
     }
 
     fn append_trailer(
-        mut writer: &mut ChecksummedWriter<BufWriter<File>>,
+        writer: &mut ChecksummedWriter<BufWriter<File>>,
         toc: &[TocEntry],
-    ) -> crate::Result<()> {
-        // Write ToC
+        checksum_type: ChecksumType,
+        flags: u8,
+        digests: &TrailerDigests,
+        #[cfg(feature = "signing")] signing_key: Option<&SigningKey>,
+    ) -> crate::Result<(u64, u64)> {
+        let mut toc_bytes = vec![];
+        let toc_checksum = TocWriter::write_into(&mut toc_bytes, toc, checksum_type)?;
+        let toc_len = toc_bytes.len() as u64;
+
+        // Write ToC mirror
+        if flags & FLAG_TOC_MIRROR != 0 {
+            let mirror_pos = writer.inner().stream_position()?;
+
+            writer.write_all(&toc_bytes)?;
+            // NOTE: The mirrored ToC is not followed by a signature
+            TrailerWriter::write_into(
+                &mut *writer,
+                toc_checksum,
+                mirror_pos,
+                toc_len,
+                flags & !FLAG_SIGNED,
+                digests,
+            )?;
+
+            // NOTE: Keep the mirror and the ToC on separate pages
+            let mirror_end = writer.inner().stream_position()?;
+            let padding = mirror_end.next_multiple_of(MIRROR_ALIGNMENT) - mirror_end;
+
+            #[allow(clippy::cast_possible_truncation)]
+            writer.write_all(&vec![0; padding as usize])?;
+        }
+
         let toc_pos = writer.inner().stream_position()?;
-        let toc_checksum = TocWriter::write_into(&mut writer, toc)?;
 
-        let after_toc_pos = writer.inner().stream_position()?;
-        let toc_len = after_toc_pos - toc_pos;
+        let mut trailer_bytes = vec![];
+        TrailerWriter::write_into(
+            &mut trailer_bytes,
+            toc_checksum,
+            toc_pos,
+            toc_len,
+            flags,
+            digests,
+        )?;
+
+        // Write ToC
+        writer.write_all(&toc_bytes)?;
+
+        // Write signature
+        #[cfg(feature = "signing")]
+        if let Some(key) = signing_key {
+            writer.write_all(&crate::signature::sign(key, &toc_bytes, &trailer_bytes))?;
+        }
 
         // Write trailer
-        TrailerWriter::write_into(writer, toc_checksum, toc_pos, toc_len)
+        writer.write_all(&trailer_bytes)?;
+
+        Ok((toc_pos, toc_len))
     }
 
     /// Finishes the file.
     ///
-    /// Returns a full-file checksum.
+    /// Returns a summary of the archive, including the full-file checksum.
     ///
     /// # Errors
     ///
-    /// Returns error, if an IO error occurred.
+    /// Returns error, if an IO error occurred, or the parity layout is invalid
+    /// (see [`Writer::use_parity`]). In the latter case, the archive is dropped unfinished.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn finish(self) -> crate::Result<FinishedArchive> {
+        self.finish_inner(
+            #[cfg(feature = "signing")]
+            None,
+        )
+    }
+
+    /// Finishes the file and signs the table of contents and trailer with the given key.
+    ///
+    /// Because the table of contents contains the section checksums, the signature
+    /// covers all section contents as well. This requires a cryptographic [`ChecksumType`]
+    /// (see [`ChecksumType::is_cryptographic`]), so the section contents are protected
+    /// against deliberate tampering.
+    ///
+    /// Returns a summary of the archive, including the full-file checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the checksum type is not cryptographic,
+    /// or the parity layout is invalid. In the latter cases, the archive is dropped unfinished.
+    #[cfg(feature = "signing")]
     #[allow(clippy::missing_panics_doc)]
-    pub fn finish(mut self) -> crate::Result<Checksum> {
+    pub fn finish_signed(self, key: &SigningKey) -> crate::Result<FinishedArchive> {
+        if !self.checksum_type.is_cryptographic() {
+            log::error!(
+                "Cannot sign archive with non-cryptographic checksum type {:?}",
+                self.checksum_type,
+            );
+            return Err(crate::Error::InsecureChecksumType {
+                path: self.file_path().map(Path::to_path_buf),
+                checksum_type: self.checksum_type,
+            });
+        }
+
+        self.finish_inner(Some(key))
+    }
+
+    /// Aborts the archive and removes the partially written file.
+    ///
+    /// Writers created with [`Writer::from_writer`] do not know the path of their file,
+    /// so the file is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn abort(mut self) -> crate::Result<()> {
+        self.finished = true;
+
+        if let Some(path) = self.file_path() {
+            log::debug!("Removing aborted archive {}", path.display());
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish_inner(
+        mut self,
+        #[cfg(feature = "signing")] signing_key: Option<&SigningKey>,
+    ) -> crate::Result<FinishedArchive> {
+        if let Some(layout) = self.parity.filter(|layout| !layout.is_valid()) {
+            log::error!("Cannot finish archive with invalid parity layout {layout:?}");
+            return Err(crate::Error::InvalidParityLayout {
+                path: self.file_path().map(Path::to_path_buf),
+                shard_size: layout.shard_size,
+                data_shards: layout.data_shards,
+                parity_shards: layout.parity_shards,
+            });
+        }
+
         self.append_toc_entry()?;
-        Self::append_trailer(&mut self.writer, &self.toc)?;
+
+        let mut flags = 0;
+        let mut digests = TrailerDigests::default();
+
+        if let Some(layout) = self.parity {
+            // NOTE: Buffered data needs to be written before reading it back
+            self.writer.flush()?;
+
+            let file = self.writer.inner().get_ref().try_clone()?;
+            digests.parity = Some(crate::parity::write_into(
+                &mut self.writer,
+                &file,
+                &DataStream::new(&self.toc),
+                layout,
+                self.checksum_type,
+            )?);
+            flags |= FLAG_PARITY;
+        }
+
+        if !self.block_index.is_empty() {
+            digests.block_index = Some(crate::block_index::write_into(
+                &mut self.writer,
+                &self.block_index,
+                self.checksum_type,
+            )?);
+            flags |= FLAG_BLOCK_CHECKSUMS;
+        }
+
+        if self.section_markers {
+            flags |= FLAG_SECTION_MARKERS;
+        }
+
+        if self.toc_mirror {
+            flags |= FLAG_TOC_MIRROR;
+        }
+
+        if self.toc.iter().any(TocEntry::is_alias) {
+            flags |= FLAG_ALIASES;
+        }
+
+        #[cfg(feature = "signing")]
+        if signing_key.is_some() {
+            flags |= FLAG_SIGNED;
+        }
+
+        #[allow(clippy::expect_used)]
+        let merkle_root = self.merkle_root.then(|| {
+            let leaves = crate::merkle::leaves(&self.toc)
+                .expect("section checksums should be set by writer");
+            crate::merkle::root(self.checksum_type, &leaves)
+        });
+
+        if merkle_root.is_some() {
+            digests.merkle_root = merkle_root;
+            flags |= FLAG_MERKLE_ROOT;
+        }
+
+        let (toc_pos, toc_len) = Self::append_trailer(
+            &mut self.writer,
+            &self.toc,
+            self.checksum_type,
+            flags,
+            &digests,
+            #[cfg(feature = "signing")]
+            signing_key,
+        )?;
 
         // Flush & sync
+        self.writer.flush()?;
+
+        let file = self.writer.inner().get_mut();
+
         log::trace!("Syncing file");
 
-        self.writer.flush()?;
-        self.writer.inner().get_mut().sync_all()?;
+        match &self.durability {
+            Durability::None => {}
+            Durability::SyncData => file.sync_data()?,
+            Durability::SyncAll => file.sync_all()?,
+            Durability::Group(group) => {
+                group.defer(file.try_clone()?, self.path.clone(), self.atomic.take());
+            }
+        }
+
+        // NOTE: Atomic writers in a group are moved into place when the group is committed
+        if let Some(target) = &self.atomic {
+            let result = if matches!(self.durability, Durability::None) {
+                target.persist()
+            } else {
+                target.commit()
+            };
+
+            // NOTE: Unless the destination exists, the temporary file is kept, so the archive is not lost
+            if let Err(e) = result {
+                if e.kind() != std::io::ErrorKind::AlreadyExists {
+                    self.drop_behavior = DropBehavior::Keep;
+                }
+                return Err(e.into());
+            }
+        }
 
-        Ok(self.writer.checksum())
+        let file_size = self.writer.inner().stream_position()?;
+        self.finished = true;
+
+        Ok(FinishedArchive {
+            file_size,
+            toc_pos,
+            toc_len,
+            checksum: self.writer.checksum(),
+            merkle_root,
+            toc: Toc(std::mem::take(&mut self.toc)),
+        })
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let path = self.file_path();
+
+        match self.drop_behavior {
+            DropBehavior::Keep => {}
+            DropBehavior::Log => {
+                if let Some(path) = path {
+                    log::warn!(
+                        "Writer was dropped without finishing, leaving incomplete archive at {}",
+                        path.display(),
+                    );
+                } else {
+                    log::warn!("Writer was dropped without finishing, leaving incomplete archive");
+                }
+            }
+            DropBehavior::Delete => {
+                let Some(path) = path else {
+                    return;
+                };
+
+                log::debug!("Removing incomplete archive {}", path.display());
+
+                // NOTE: The file may already be gone, e.g. if moving an atomic writer's file failed
+                if let Err(e) = std::fs::remove_file(path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        log::error!("Failed to remove incomplete archive: {e:?}");
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -137,6 +1201,7 @@ mod tests {
     use super::*;
     use crate::toc::reader::TocReader;
     use crate::trailer::reader::TrailerReader;
+    use crate::ReaderOptions;
     use std::io::Write;
     use test_log::test;
 
@@ -152,7 +1217,7 @@ mod tests {
         let trailer = TrailerReader::from_reader(&mut reader)?;
         assert_eq!(0, trailer.toc_pos);
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer, &ReaderOptions::default())?;
         assert_eq!(0, toc.len());
         assert!(toc.is_empty());
         assert!(toc.section(b"hello").is_none());
@@ -168,14 +1233,14 @@ mod tests {
         let data = b"hello world";
 
         let mut writer = Writer::new_at_path(&path)?;
-        writer.write_all(data)?;
+        writer.section("")?.write_all(data)?;
         writer.finish()?;
 
         let mut reader = File::open(&path)?;
         let trailer = TrailerReader::from_reader(&mut reader)?;
         assert_eq!(data.len() as u64, trailer.toc_pos);
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer, &ReaderOptions::default())?;
         assert_eq!(1, toc.len());
         assert!(toc.section(b"hello").is_none());
         assert!(toc.section(b"").is_some());
@@ -197,11 +1262,9 @@ mod tests {
         let data3 = b"hello world3";
 
         let mut writer = Writer::new_at_path(&path)?;
-        writer.write_all(data)?;
-        writer.start("section1")?;
-        writer.write_all(data2)?;
-        writer.start("section2")?;
-        writer.write_all(data3)?;
+        writer.section("")?.write_all(data)?;
+        writer.section("section1")?.write_all(data2)?;
+        writer.section("section2")?.write_all(data3)?;
         writer.finish()?;
 
         let mut reader = File::open(&path)?;
@@ -211,7 +1274,7 @@ mod tests {
             trailer.toc_pos,
         );
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer, &ReaderOptions::default())?;
         assert_eq!(3, toc.len());
         assert!(toc.section(b"hello").is_none());
         assert!(toc.section(b"").is_some());
@@ -0,0 +1,175 @@
+use sfa::{DropBehavior, Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn abort_removes_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.abort()?;
+
+    assert!(!path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+pub fn abort_atomic() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_atomic(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.abort()?;
+
+    assert_eq!(0, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+pub fn drop_behavior() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for (behavior, exists) in [
+        (DropBehavior::Keep, true),
+        (DropBehavior::Log, true),
+        (DropBehavior::Delete, false),
+    ] {
+        let path = dir.path().join(format!("cherry_pie_{behavior:?}"));
+
+        {
+            let mut writer = Writer::new_at_path(&path)?.use_drop_behavior(behavior);
+            writer
+                .section("Verse 1")?
+                .write_all(b"Glazed eyes and cherry pie\n")?;
+        }
+
+        assert_eq!(exists, path.try_exists()?, "{behavior:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn drop_atomic_removes_temp_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    {
+        let mut writer = Writer::new_atomic(&path)?;
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+    }
+
+    assert_eq!(0, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+pub fn drop_after_finish_keeps_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?.use_drop_behavior(DropBehavior::Delete);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    assert_eq!(1, Reader::new(&path)?.toc().len());
+
+    Ok(())
+}
+
+#[test]
+pub fn incomplete_archive() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for data in [&b""[..], b"Glazed", b"Glazed eyes and cherry pie\n"] {
+        let path = dir.path().join("cherry_pie");
+
+        {
+            let mut writer = Writer::new_at_path(&path)?.use_drop_behavior(DropBehavior::Keep);
+            writer.section("Verse 1")?.write_all(data)?;
+        }
+
+        assert!(matches!(
+            Reader::new(&path),
+            Err(sfa::Error::IncompleteArchive { len, path: Some(p) }) if len == data.len() as u64 && p == path,
+        ));
+
+        std::fs::remove_file(&path)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn truncated_trailer_is_not_incomplete() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for merkle_root in [false, true] {
+        let path = dir.path().join(format!("cherry_pie_{merkle_root}"));
+
+        let mut writer = Writer::new_at_path(&path)?.use_merkle_root(merkle_root);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+        writer.finish()?;
+
+        let bytes = std::fs::read(&path)?;
+
+        for missing in [1, 3, 13] {
+            std::fs::write(&path, &bytes[..bytes.len() - missing])?;
+
+            // NOTE: The archive was finished, so it must not be reported as incomplete
+            assert!(
+                matches!(
+                    Reader::new(&path),
+                    Err(sfa::Error::TruncatedArchive { len, expected_len, .. })
+                        if len == (bytes.len() - missing) as u64 && expected_len == bytes.len() as u64
+                ),
+                "{missing} bytes missing",
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn corrupted_trailer_magic_is_not_incomplete() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for merkle_root in [false, true] {
+        let path = dir.path().join(format!("cherry_pie_{merkle_root}"));
+
+        let mut writer = Writer::new_at_path(&path)?.use_merkle_root(merkle_root);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+        writer.finish()?;
+
+        let mut bytes = std::fs::read(&path)?;
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&[0; 4]);
+        std::fs::write(&path, &bytes)?;
+
+        // NOTE: The archive was finished, so it must not be reported as incomplete
+        assert!(matches!(
+            Reader::new(&path),
+            Err(sfa::Error::InvalidTrailerMagic { .. })
+        ));
+    }
+
+    Ok(())
+}
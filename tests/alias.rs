@@ -0,0 +1,86 @@
+mod common;
+
+use common::read_section;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn alias() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for section_markers in [false, true] {
+        let path = dir.path().join(format!("cherry_pie_{section_markers}"));
+
+        let mut writer = Writer::new_at_path(&path)?.use_section_markers(section_markers);
+        writer
+            .section("v1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+        let mut section = writer.section("v2")?;
+        section.write_all(b"Youth is running out, we finally feel it now\n")?;
+        let v2 = section.finish()?;
+
+        let latest = writer.alias("latest", "v2")?;
+        assert_eq!(b"latest", latest.name());
+        assert_eq!(v2.pos(), latest.pos());
+        assert_eq!(v2.checksum(), latest.checksum());
+
+        let word = writer.alias_range("youth", "latest", 0, 5)?;
+        assert_eq!(v2.pos(), word.pos());
+        assert_eq!(5, word.len());
+
+        writer
+            .section("v3")?
+            .write_all(b"There's a hush now in our hearts\n")?;
+        writer.alias_range("hush", "v3", 10, 4)?;
+        writer.finish()?;
+
+        let reader = Reader::new(&path)?;
+        reader.verify(&path)?;
+        assert!(Reader::validate(&path)?.is_valid());
+
+        let toc = reader.toc();
+        assert_eq!(6, toc.len());
+        assert_eq!(
+            [false, false, true, true, false, true],
+            *toc.iter().map(sfa::TocEntry::is_alias).collect::<Vec<_>>(),
+        );
+        assert_eq!(b"latest", toc[2].name());
+        assert_eq!(
+            b"Youth is running out, we finally feel it now\n",
+            &*read_section(&reader, &path, 2)?,
+        );
+        assert_eq!(b"youth", toc[3].name());
+        assert_eq!(b"Youth", &*read_section(&reader, &path, 3)?);
+        assert_eq!(b"hush", toc[5].name());
+        assert_eq!(b"hush", &*read_section(&reader, &path, 5)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn alias_invalid() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("v1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+
+    for result in [
+        writer.alias("latest", "v2"),
+        writer.alias_range("latest", "v1", 20, 8),
+        writer.alias_range("latest", "v1", u64::MAX, 1),
+    ] {
+        assert!(matches!(
+            result,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidInput,
+        ));
+    }
+
+    writer.alias_range("latest", "v1", 20, 7)?;
+    writer.finish()?;
+
+    Ok(())
+}
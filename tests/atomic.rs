@@ -0,0 +1,71 @@
+use sfa::{OverwritePolicy, Reader, Writer};
+use std::io::Write;
+
+fn write_archive(writer: &mut Writer, content: &[u8]) -> Result<(), sfa::Error> {
+    writer.section("Verse 1")?.write_all(content)?;
+    Ok(())
+}
+
+fn dir_entries(dir: &std::path::Path) -> Result<Vec<String>, sfa::Error> {
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+#[test]
+pub fn atomic_create() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_atomic(&path)?;
+    write_archive(&mut writer, b"Glazed eyes and cherry pie\n")?;
+
+    // The archive is not visible before it is finished
+    assert!(!path.try_exists()?);
+
+    writer.finish()?;
+    assert_eq!(["cherry_pie"], &*dir_entries(dir.path())?);
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(b"Verse 1", reader.toc()[0].name());
+    reader.verify(&path)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn atomic_overwrite_fail() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    std::fs::write(&path, b"existing")?;
+
+    let mut writer = Writer::new_atomic(&path)?;
+    write_archive(&mut writer, b"Glazed eyes and cherry pie\n")?;
+
+    assert!(matches!(
+        writer.finish(),
+        Err(sfa::Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+    assert_eq!(b"existing", &*std::fs::read(&path)?);
+    assert_eq!(["cherry_pie"], &*dir_entries(dir.path())?);
+
+    Ok(())
+}
+
+#[test]
+pub fn atomic_overwrite_replace() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    std::fs::write(&path, b"existing")?;
+
+    let mut writer = Writer::new_atomic(&path)?.use_overwrite_policy(OverwritePolicy::Replace);
+    write_archive(&mut writer, b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    assert_eq!(["cherry_pie"], &*dir_entries(dir.path())?);
+    Reader::new(&path)?.verify(&path)?;
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use common::data;
+use sfa::{RandomAccessSource, Reader, Writer};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+const BLOCK_SIZE: u32 = 1_024;
+
+fn write_archive(
+    path: &std::path::Path,
+    section_markers: bool,
+    toc_mirror: bool,
+) -> Result<sfa::FinishedArchive, sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?
+        .use_block_checksums(BLOCK_SIZE)
+        .use_section_markers(section_markers)
+        .use_toc_mirror(toc_mirror)
+        .use_dedup(true);
+
+    writer
+        .section("small")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("large")?.write_all(&data(10_500))?;
+    writer.section("empty")?.finish()?;
+    writer.section("copy")?.write_all(&data(10_500))?;
+    writer.alias("alias", "large")?;
+    writer.alias_range("range", "large", 10, 20)?;
+
+    writer.finish()
+}
+
+#[test]
+pub fn block_checksums() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for (section_markers, toc_mirror) in [(false, false), (true, false), (false, true)] {
+        let path = dir
+            .path()
+            .join(format!("cherry_pie_{section_markers}_{toc_mirror}"));
+        let archive = write_archive(&path, section_markers, toc_mirror)?;
+
+        assert_eq!(
+            xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+            archive.checksum().into_u128(),
+        );
+
+        let reader = Reader::new(&path)?;
+        reader.verify(&path)?;
+        assert!(Reader::validate(&path)?.is_valid());
+
+        let toc = reader.toc();
+        let file = std::fs::File::open(&path)?;
+
+        let mut small = reader.verified_reader(&toc[0], &file).unwrap();
+        let mut buf = vec![];
+        small.read_to_end(&mut buf)?;
+        assert_eq!(b"Glazed eyes and cherry pie\n", &*buf);
+
+        for idx in [1, 3, 4] {
+            let mut section = reader.verified_reader(&toc[idx], &file).unwrap();
+            assert_eq!(BLOCK_SIZE, section.block_size());
+
+            let mut buf = vec![];
+            section.read_to_end(&mut buf)?;
+            assert_eq!(data(10_500), buf);
+
+            let mut buf = [0; 3_000];
+            section.seek(SeekFrom::Start(5_000))?;
+            section.read_exact(&mut buf)?;
+            assert_eq!(data(10_500)[5_000..8_000], buf);
+
+            let mut buf = [0; 600];
+            section.read_at(9_900, &mut buf)?;
+            assert_eq!(data(10_500)[9_900..], buf);
+
+            assert!(section.read_at(10_000, &mut buf).is_err());
+        }
+
+        assert!(reader.verified_reader(&toc[2], &file).is_none());
+        assert!(reader.verified_reader(&toc[5], &file).is_none());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn block_checksums_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    let reader = Reader::new(&path)?;
+    let entry = &reader.toc()[1];
+
+    // Corrupt block 3
+    let mut bytes = std::fs::read(&path)?;
+    let corrupted_pos = entry.pos() + 3 * u64::from(BLOCK_SIZE) + 10;
+    bytes[corrupted_pos as usize] ^= 1;
+
+    let section = reader.verified_reader(entry, &bytes).unwrap();
+
+    // Blocks before and after the corrupted block can still be read
+    let mut buf = vec![0; 2 * BLOCK_SIZE as usize];
+    section.read_at(0, &mut buf)?;
+    section.read_at(4 * u64::from(BLOCK_SIZE), &mut buf)?;
+
+    let mut buf = [0; 10];
+    let e = section.read_at(3_500, &mut buf).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, e.kind());
+
+    let Some(sfa::Error::BlockChecksumMismatch {
+        section,
+        block,
+        offset,
+        ..
+    }) = e.get_ref().and_then(|e| e.downcast_ref::<sfa::Error>())
+    else {
+        panic!("expected block checksum mismatch");
+    };
+    assert_eq!(b"large", &**section);
+    assert_eq!(3, *block);
+    assert_eq!(entry.pos() + 3 * u64::from(BLOCK_SIZE), *offset);
+
+    let mut section = reader.verified_reader(entry, &bytes).unwrap();
+    let mut buf = vec![];
+    assert!(section.read_to_end(&mut buf).is_err());
+    assert_eq!(3 * BLOCK_SIZE as usize, buf.len());
+
+    Ok(())
+}
+
+#[test]
+pub fn block_index_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, false, false)?;
+
+    // Corrupt the last block checksum, which is located before the block index footer
+    let mut bytes = std::fs::read(&path)?;
+    bytes[archive.toc_pos() as usize - 30] ^= 1;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::InvalidBlockIndex { .. }),
+    ));
+    assert!(Reader::validate(&path)?
+        .issues()
+        .contains(&sfa::ValidationIssue::InvalidBlockIndex));
+
+    Ok(())
+}
+
+#[test]
+pub fn block_index_forged() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, false, false)?;
+
+    // Replace the last block checksum, and fix up the checksum in the block index footer,
+    // which does not help, because the block index digest is stored in the trailer
+    let mut bytes = std::fs::read(&path)?;
+    let footer = archive.toc_pos() as usize - 24;
+    let len = u64::from_le_bytes(bytes[footer + 4..footer + 12].try_into().unwrap()) as usize;
+
+    bytes[footer - 1] ^= 1;
+    let checksum = xxhash_rust::xxh3::xxh3_64(&bytes[footer - len..footer]);
+    bytes[footer + 12..footer + 20].copy_from_slice(&checksum.to_le_bytes());
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::InvalidBlockIndex { .. }),
+    ));
+    assert!(Reader::validate(&path)?
+        .issues()
+        .contains(&sfa::ValidationIssue::InvalidBlockIndex));
+
+    Ok(())
+}
+
+#[test]
+pub fn block_index_too_large() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    let options = sfa::ReaderOptions::default().max_block_index_size(100);
+
+    assert!(matches!(
+        Reader::with_options(&path, &options),
+        Err(sfa::Error::BlockIndexTooLarge { limit: 100, .. }),
+    ));
+    assert!(matches!(
+        Reader::validate_with_options(&path, &options),
+        Err(sfa::Error::BlockIndexTooLarge { limit: 100, .. }),
+    ));
+
+    Ok(())
+}
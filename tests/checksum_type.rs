@@ -0,0 +1,93 @@
+mod common;
+
+use common::{fix_trailer_checksum, TRAILER_SIZE};
+use sfa::{Checksum, ChecksumType, Reader, Writer};
+use std::io::{Read, Write};
+
+/// Writes and reads back a small archive, returning the full-file checksum and the file contents
+fn roundtrip(checksum_type: ChecksumType) -> Result<(Checksum, Vec<u8>), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("chksum");
+
+    let mut writer = Writer::new_at_path(&path)?.use_checksum_type(checksum_type);
+    writer.section("Hello")?.write_all(b"World")?;
+    let checksum = writer.finish()?.checksum();
+    assert_eq!(checksum_type, checksum.checksum_type());
+    assert_eq!(checksum_type.digest_len(), checksum.as_bytes().len());
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(checksum_type, reader.checksum_type());
+    assert_eq!(1, reader.toc().len());
+
+    let bytes = reader.toc()[0]
+        .buf_reader(&path)?
+        .bytes()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(bytes, b"World");
+
+    Ok((checksum, std::fs::read(&path)?))
+}
+
+#[test]
+pub fn checksum_type_xxh3() -> Result<(), sfa::Error> {
+    let (checksum, file_contents) = roundtrip(ChecksumType::Xxh3)?;
+    assert_eq!(
+        checksum.into_u128(),
+        xxhash_rust::xxh3::xxh3_128(&file_contents)
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "crc32c")]
+pub fn checksum_type_crc32c() -> Result<(), sfa::Error> {
+    let (checksum, file_contents) = roundtrip(ChecksumType::Crc32c)?;
+    assert_eq!(
+        checksum.as_bytes(),
+        crc32c::crc32c(&file_contents).to_le_bytes()
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "blake3")]
+pub fn checksum_type_blake3() -> Result<(), sfa::Error> {
+    let (checksum, file_contents) = roundtrip(ChecksumType::Blake3)?;
+    assert_eq!(checksum.as_bytes(), blake3::hash(&file_contents).as_bytes());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "sha256")]
+pub fn checksum_type_sha256() -> Result<(), sfa::Error> {
+    use sha2::Digest;
+
+    let (checksum, file_contents) = roundtrip(ChecksumType::Sha256)?;
+    assert_eq!(checksum.as_bytes(), &*sha2::Sha256::digest(&file_contents));
+    Ok(())
+}
+
+#[test]
+pub fn checksum_type_unsupported() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("chksum");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer.section("Hello")?.write_all(b"World")?;
+    writer.finish()?;
+
+    // Overwrite checksum type byte in trailer, and fix up the trailer checksum
+    let mut file_contents = std::fs::read(&path)?;
+    let trailer_pos = file_contents.len() - TRAILER_SIZE;
+    let trailer = &mut file_contents[trailer_pos..];
+    trailer[49] = 0xFF;
+    fix_trailer_checksum(trailer);
+    std::fs::write(&path, &file_contents)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::UnsupportedChecksumType { .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Helpers shared by the integration tests
+
+// NOTE: Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use sfa::{Reader, Writer};
+use std::io::{Read, Write};
+
+/// Reads the section at `idx` of the archive at `path`.
+pub fn read_section(
+    reader: &Reader,
+    path: &std::path::Path,
+    idx: usize,
+) -> std::io::Result<Vec<u8>> {
+    reader.toc()[idx]
+        .buf_reader(path)?
+        .bytes()
+        .collect::<std::io::Result<Vec<_>>>()
+}
+
+/// Size of the (version 2) trailer
+pub const TRAILER_SIZE: usize = 63;
+
+/// Size of the trailer fields covered by the trailer checksum
+pub const TRAILER_CHECKSUMMED_SIZE: usize = 50;
+
+/// Size of the version 3 trailer, which additionally contains three 32-byte digests
+pub const TRAILER_V3_SIZE: usize = TRAILER_SIZE + 3 * 32;
+
+/// Sections of the archive written by [`write_cherry_pie`]
+pub const CHERRY_PIE: &[(&str, &[u8])] = &[
+    ("Verse 1", b"Glazed eyes and cherry pie\n"),
+    ("Chorus", b"Youth is running out, we finally feel it now\n"),
+];
+
+/// Writes the given sections.
+pub fn write_sections(writer: &mut Writer, sections: &[(&str, &[u8])]) -> std::io::Result<()> {
+    for (name, data) in sections {
+        writer.section(*name)?.write_all(data)?;
+    }
+    Ok(())
+}
+
+/// Writes an archive with the given sections to `path`.
+pub fn write_archive(path: &std::path::Path, sections: &[(&str, &[u8])]) -> Result<(), sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?;
+    write_sections(&mut writer, sections)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes an archive with the [`CHERRY_PIE`] sections to `path`, returning its bytes.
+pub fn write_cherry_pie(path: &std::path::Path) -> Result<Vec<u8>, sfa::Error> {
+    write_archive(path, CHERRY_PIE)?;
+    Ok(std::fs::read(path)?)
+}
+
+/// Returns `len` bytes of test data.
+pub fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|idx| (idx % 251) as u8).collect()
+}
+
+/// Fixes up the checksum of a (version 2 or 3) trailer after modifying its fields.
+pub fn fix_trailer_checksum(trailer: &mut [u8]) {
+    let checksummed_size = trailer.len() - 8 - 1 - 4;
+    let trailer_checksum = xxhash_rust::xxh3::xxh3_64(&trailer[..checksummed_size]);
+    trailer[checksummed_size..checksummed_size + 8]
+        .copy_from_slice(&trailer_checksum.to_le_bytes());
+}
+
+/// Returns the section names of the archive.
+pub fn names(reader: &Reader) -> Vec<Vec<u8>> {
+    reader
+        .toc()
+        .iter()
+        .map(|entry| entry.name().to_vec())
+        .collect()
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::read_section;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+fn write_archive(path: &std::path::Path) -> Result<(), sfa::Error> {
+    common::write_archive(
+        path,
+        &[
+            ("Verse 1", b"Glazed eyes and cherry pie\n"),
+            ("Chorus", b"Youth is running out, we finally feel it now\n"),
+            ("Outro", b"There's a hush now in our hearts\n"),
+        ],
+    )
+}
+
+#[test]
+pub fn copy_section_from() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let src_path = dir.path().join("cherry_pie");
+    let path = dir.path().join("cherry_pie_filtered");
+    write_archive(&src_path)?;
+
+    let src = Reader::new(&src_path)?;
+
+    let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+    writer.section("Intro")?.write_all(b"Wallows\n")?;
+    for entry in src.toc().iter().filter(|entry| entry.name() != b"Chorus") {
+        let copied = writer.copy_section_from(&src_path, entry)?;
+        assert_eq!(entry.name(), copied.name());
+        assert_eq!(entry.len(), copied.len());
+        assert_eq!(entry.checksum(), copied.checksum());
+    }
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(3, toc.len());
+    assert_eq!(b"Intro", toc[0].name());
+    assert_eq!(b"Verse 1", toc[1].name());
+    assert_eq!(b"Outro", toc[2].name());
+
+    assert_eq!(
+        b"Glazed eyes and cherry pie\n",
+        &*read_section(&reader, &path, 1)?
+    );
+    assert_eq!(
+        b"There's a hush now in our hearts\n",
+        &*read_section(&reader, &path, 2)?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn copy_section_from_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let src_path = dir.path().join("cherry_pie");
+    let path = dir.path().join("cherry_pie_copy");
+    write_archive(&src_path)?;
+
+    let src = Reader::new(&src_path)?;
+
+    let mut bytes = std::fs::read(&src_path)?;
+    bytes[30] ^= 1;
+    std::fs::write(&src_path, &bytes)?;
+
+    // The stored checksum is copied, so the corruption is not hidden
+    let mut writer = Writer::new_at_path(&path)?;
+    for entry in src.toc().iter() {
+        let copied = writer.copy_section_from(&src_path, entry)?;
+        assert_eq!(entry.checksum(), copied.checksum());
+    }
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    assert!(matches!(
+        reader.verify(&path),
+        Err(sfa::Error::ChecksumMismatch { path: Some(p), section: Some(section), .. })
+            if p == path && section == b"Chorus",
+    ));
+
+    Ok(())
+}
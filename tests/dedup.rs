@@ -0,0 +1,129 @@
+mod common;
+
+use common::read_section;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+const DICT: &[u8] = b"Glazed eyes and cherry pie\n";
+const CHORUS: &[u8] = b"Youth is running out, we finally feel it now\n";
+
+fn write_archive(
+    path: &std::path::Path,
+    dedup: bool,
+    section_markers: bool,
+) -> Result<sfa::FinishedArchive, sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?
+        .use_dedup(dedup)
+        .use_section_markers(section_markers);
+
+    for partition in 0..3 {
+        writer
+            .section(format!("{partition}/dict"))?
+            .write_all(DICT)?;
+        writer
+            .section(format!("{partition}/data"))?
+            .write_all(format!("Partition {partition}\n").as_bytes())?;
+    }
+
+    writer.section("chorus")?.write_all(CHORUS)?;
+    writer.section("dict")?.write_all(DICT)?;
+
+    writer.finish()
+}
+
+#[test]
+pub fn dedup() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for section_markers in [false, true] {
+        let plain_path = dir.path().join(format!("plain_{section_markers}"));
+        let path = dir.path().join(format!("dedup_{section_markers}"));
+
+        let plain = write_archive(&plain_path, false, section_markers)?;
+        let archive = write_archive(&path, true, section_markers)?;
+        assert!(archive.file_size() < plain.file_size());
+
+        let bytes = std::fs::read(&path)?;
+        assert_eq!(bytes.len() as u64, archive.file_size());
+        assert_eq!(
+            xxhash_rust::xxh3::xxh3_128(&bytes),
+            archive.checksum().into_u128(),
+        );
+
+        let reader = Reader::new(&path)?;
+        reader.verify(&path)?;
+        assert!(Reader::validate(&path)?.is_valid());
+
+        let toc = reader.toc();
+        assert_eq!(8, toc.len());
+
+        for idx in [0, 2, 4, 7] {
+            assert_eq!(toc[0].pos(), toc[idx].pos());
+            assert_eq!(DICT, read_section(&reader, &path, idx)?);
+        }
+        assert_eq!(b"Partition 2\n", &*read_section(&reader, &path, 5)?);
+        assert_eq!(CHORUS, read_section(&reader, &path, 6)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn dedup_salvage() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, true, true)?;
+
+    let reader = Reader::salvage(&path)?;
+    let names = reader
+        .toc()
+        .iter()
+        .map(|entry| entry.name().to_vec())
+        .collect::<Vec<_>>();
+
+    // NOTE: Deduplicated sections have no marker
+    assert_eq!(
+        [
+            b"0/dict".to_vec(),
+            b"0/data".to_vec(),
+            b"1/data".to_vec(),
+            b"2/data".to_vec(),
+            b"chorus".to_vec(),
+        ],
+        *names,
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "crc32c")]
+pub fn dedup_checksum_collision() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("collision");
+
+    // NOTE: Both have the same CRC32C
+    let a = b"butlyrqwveil";
+    let b = b"mardefvcwdyf";
+
+    let mut writer = Writer::new_at_path(&path)?
+        .use_checksum_type(sfa::ChecksumType::Crc32c)
+        .use_dedup(true);
+    writer.section("a")?.write_all(a)?;
+    writer.section("b")?.write_all(b)?;
+    writer.section("c")?.write_all(a)?;
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(toc[0].checksum(), toc[1].checksum());
+    assert_ne!(toc[0].pos(), toc[1].pos());
+    assert_eq!(toc[0].pos(), toc[2].pos());
+    assert_eq!(a, &*read_section(&reader, &path, 0)?);
+    assert_eq!(b, &*read_section(&reader, &path, 1)?);
+    assert_eq!(a, &*read_section(&reader, &path, 2)?);
+
+    Ok(())
+}
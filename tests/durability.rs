@@ -0,0 +1,94 @@
+mod common;
+
+use common::{write_sections, CHERRY_PIE};
+use sfa::{Durability, Reader, SyncGroup, Writer};
+
+#[test]
+pub fn durability_modes() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for (idx, durability) in [Durability::None, Durability::SyncData, Durability::SyncAll]
+        .into_iter()
+        .enumerate()
+    {
+        let path = dir.path().join(format!("cherry_pie_{idx}"));
+
+        let mut writer = Writer::new_at_path(&path)?.use_durability(durability);
+        write_sections(&mut writer, CHERRY_PIE)?;
+        writer.finish()?;
+
+        Reader::new(&path)?.verify(&path)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn durability_group() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let group = SyncGroup::default();
+
+    let plain_path = dir.path().join("plain");
+    let mut writer =
+        Writer::new_at_path(&plain_path)?.use_durability(Durability::Group(group.clone()));
+    write_sections(&mut writer, CHERRY_PIE)?;
+    writer.finish()?;
+
+    let atomic_path = dir.path().join("atomic");
+    let mut writer =
+        Writer::new_atomic(&atomic_path)?.use_durability(Durability::Group(group.clone()));
+    write_sections(&mut writer, CHERRY_PIE)?;
+    writer.finish()?;
+
+    assert_eq!(2, group.len());
+
+    // Atomic archives become visible once they are durable
+    Reader::new(&plain_path)?.verify(&plain_path)?;
+    assert!(!atomic_path.try_exists()?);
+
+    group.commit()?;
+    assert!(group.is_empty());
+
+    Reader::new(&atomic_path)?.verify(&atomic_path)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn durability_group_partial_failure() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let group = SyncGroup::default();
+
+    let paths = (0..3)
+        .map(|idx| dir.path().join(format!("cherry_pie_{idx}")))
+        .collect::<Vec<_>>();
+
+    for path in &paths {
+        let mut writer = Writer::new_atomic(path)?.use_durability(Durability::Group(group.clone()));
+        write_sections(&mut writer, CHERRY_PIE)?;
+        writer.finish()?;
+    }
+
+    // The destination of the first archive is taken in the meantime
+    std::fs::write(&paths[0], b"Youth is running out")?;
+
+    let Err(sfa::Error::CommitFailed { errors }) = group.commit() else {
+        panic!("commit should fail");
+    };
+    assert_eq!(1, errors.len());
+    assert_eq!(Some(&*paths[0]), errors[0].path());
+    assert_eq!(
+        Some(std::io::ErrorKind::AlreadyExists),
+        errors[0].io_error().map(std::io::Error::kind),
+    );
+    assert!(group.is_empty());
+
+    // The other archives are committed, and no temporary files are left behind
+    assert_eq!(b"Youth is running out", &*std::fs::read(&paths[0])?);
+    for path in &paths[1..] {
+        Reader::new(path)?.verify(path)?;
+    }
+    assert_eq!(3, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
@@ -8,11 +8,13 @@ pub fn empty_section() -> Result<(), sfa::Error> {
     // let path = std::path::Path::new("test_fixture/cherry_pie_broken");
 
     let mut writer = Writer::new_at_path(&path)?;
-    writer.start("Verse 1")?;
-    writer.write_all(b"Glazed eyes and cherry pie\n")?;
-    writer.start("Verse 2")?;
-    writer.start("Chorus 2")?;
-    writer.write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("Verse 2")?.finish()?;
+    writer
+        .section("Chorus 2")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
     writer.finish()?;
 
     let reader = Reader::new(&path)?;
@@ -53,11 +55,13 @@ pub fn empty_section_name() -> Result<(), sfa::Error> {
     let path = dir.path().join("cherry_pie");
 
     let mut writer = Writer::new_at_path(&path)?;
-    writer.start("Verse 1")?;
-    writer.write_all(b"Glazed eyes and cherry pie\n")?;
-    writer.start("Verse 2")?;
-    writer.start("")?;
-    writer.write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("Verse 2")?.finish()?;
+    writer
+        .section("")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
     writer.finish()?;
 
     let reader = Reader::new(&path)?;
@@ -91,3 +95,32 @@ pub fn empty_section_name() -> Result<(), sfa::Error> {
 
     Ok(())
 }
+
+#[test]
+pub fn empty_first_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer.section("Intro")?.finish()?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+
+    assert_eq!(b"Intro", toc[0].name());
+    assert_eq!(0, toc[0].pos());
+    assert_eq!(0, toc[0].len());
+
+    assert_eq!(b"Verse 1", toc[1].name());
+    assert_eq!(0, toc[1].pos());
+    assert_eq!(27, toc[1].len());
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{fix_trailer_checksum, write_cherry_pie, TRAILER_SIZE};
+use sfa::Reader;
+
+/// Applies `f` to the ToC and fixes up the ToC and trailer checksums.
+fn modify_toc(bytes: &mut [u8], f: impl FnOnce(&mut [u8])) -> u64 {
+    let trailer_pos = bytes.len() - TRAILER_SIZE;
+    let toc_pos = u64::from_le_bytes(
+        bytes[trailer_pos + 32..trailer_pos + 40]
+            .try_into()
+            .unwrap(),
+    );
+
+    let (head, trailer) = bytes.split_at_mut(trailer_pos);
+    let toc = &mut head[toc_pos as usize..];
+    f(toc);
+
+    let toc_checksum = xxhash_rust::xxh3::xxh3_128(toc);
+    trailer[..16].copy_from_slice(&toc_checksum.to_le_bytes());
+
+    fix_trailer_checksum(trailer);
+
+    toc_pos
+}
+
+#[test]
+pub fn error_invalid_toc_magic() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let mut bytes = write_cherry_pie(&path)?;
+
+    let toc_pos = modify_toc(&mut bytes, |toc| toc[..4].copy_from_slice(b"COT!"));
+    std::fs::write(&path, &bytes)?;
+
+    let err = Reader::new(&path).err().unwrap();
+    assert!(matches!(
+        err,
+        sfa::Error::InvalidTocMagic { offset, .. } if offset == toc_pos
+    ));
+    assert_eq!(Some(path.as_path()), err.path());
+
+    Ok(())
+}
+
+#[test]
+pub fn error_section_out_of_bounds() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let mut bytes = write_cherry_pie(&path)?;
+
+    // Increase the length of "Verse 1" so it overlaps the ToC
+    modify_toc(&mut bytes, |toc| {
+        toc[16..24].copy_from_slice(&1_000u64.to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::SectionOutOfBounds { section, offset: 0, len: 1_000, .. }) if section == b"Verse 1"
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_truncated_toc() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let mut bytes = write_cherry_pie(&path)?;
+
+    // Claim there are more entries than stored
+    modify_toc(&mut bytes, |toc| {
+        toc[4..8].copy_from_slice(&3u32.to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TruncatedToc { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_display() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let reader = Reader::new(&path)?;
+    let chorus = reader.toc().section(b"Chorus").unwrap();
+
+    // Corrupt "Chorus"
+    let mut bytes = std::fs::read(&path)?;
+    bytes[chorus.pos() as usize] = b'y';
+    std::fs::write(&path, &bytes)?;
+
+    let err = reader.verify(&path).err().unwrap();
+    assert!(matches!(
+        &err,
+        sfa::Error::ChecksumMismatch { section: Some(section), .. } if section == b"Chorus"
+    ));
+    assert!(err.to_string().starts_with(&format!(
+        "{}: checksum mismatch in section \"Chorus\" at offset {}: expected ",
+        path.display(),
+        chorus.pos(),
+    )));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_io_path() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    for err in [
+        Reader::new(&path).err().unwrap(),
+        Reader::validate(&path).err().unwrap(),
+        Reader::salvage(&path).err().unwrap(),
+    ] {
+        assert!(matches!(
+            &err,
+            sfa::Error::FileIo { path: err_path, inner }
+                if *err_path == path && inner.kind() == std::io::ErrorKind::NotFound
+        ));
+        assert_eq!(Some(path.as_path()), err.path());
+        assert_eq!(
+            Some(std::io::ErrorKind::NotFound),
+            err.io_error().map(std::io::Error::kind),
+        );
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}: I/O error: ", path.display())));
+    }
+
+    Ok(())
+}
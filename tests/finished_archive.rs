@@ -0,0 +1,46 @@
+mod common;
+
+use common::TRAILER_SIZE;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn finished_archive() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for mirror in [false, true] {
+        let path = dir.path().join(format!("cherry_pie_{mirror}"));
+
+        let mut writer = Writer::new_at_path(&path)?.use_toc_mirror(mirror);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+        writer
+            .section("Chorus")?
+            .write_all(b"Youth is running out, we finally feel it now\n")?;
+        let archive = writer.finish()?;
+
+        let bytes = std::fs::read(&path)?;
+        assert_eq!(bytes.len() as u64, archive.file_size());
+        assert_eq!(
+            xxhash_rust::xxh3::xxh3_128(&bytes),
+            archive.checksum().into_u128(),
+        );
+        assert_eq!(
+            archive.file_size() - TRAILER_SIZE as u64,
+            archive.toc_pos() + archive.toc_len(),
+        );
+
+        let reader = Reader::new(&path)?;
+        assert_eq!(reader.toc().len(), archive.toc().len());
+
+        for (written, read) in archive.toc().iter().zip(reader.toc().iter()) {
+            assert_eq!(read.name(), written.name());
+            assert_eq!(read.pos(), written.pos());
+            assert_eq!(read.len(), written.len());
+            assert_eq!(read.checksum(), written.checksum());
+        }
+    }
+
+    Ok(())
+}
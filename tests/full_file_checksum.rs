@@ -8,9 +8,8 @@ pub fn full_file_checksum() -> Result<(), sfa::Error> {
     let path = dir.path().join("chksum");
 
     let mut writer = Writer::new_at_path(&path)?;
-    writer.start("Hello")?;
-    writer.write_all(b"World")?;
-    let checksum = writer.finish()?;
+    writer.section("Hello")?.write_all(b"World")?;
+    let checksum = writer.finish()?.checksum();
     let checksum = checksum.into_u128();
 
     let file_contents = std::fs::read(&path)?;
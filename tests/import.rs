@@ -0,0 +1,136 @@
+mod common;
+
+use common::read_section;
+use sfa::{Reader, Writer};
+use std::io::{BufWriter, Write};
+
+fn big_data() -> Vec<u8> {
+    (0..1_000_000u32).flat_map(u32::to_le_bytes).collect()
+}
+
+fn check_archive(path: &std::path::Path, data: &[u8]) -> Result<(), sfa::Error> {
+    let reader = Reader::new(path)?;
+    reader.verify(path)?;
+
+    let toc = reader.toc();
+    assert_eq!(3, toc.len());
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(b"Big", toc[1].name());
+    assert_eq!(b"Chorus", toc[2].name());
+
+    assert_eq!(
+        b"Glazed eyes and cherry pie\n",
+        &*read_section(&reader, path, 0)?
+    );
+    assert_eq!(data, &*read_section(&reader, path, 1)?);
+    assert_eq!(
+        b"Youth is running out, we finally feel it now\n",
+        &*read_section(&reader, path, 2)?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn import_from_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let data = big_data();
+    let src_path = dir.path().join("big");
+    std::fs::write(&src_path, &data)?;
+
+    for section_markers in [false, true] {
+        let mut writer = Writer::new_at_path(&path)?.use_section_markers(section_markers);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+
+        let entry = writer.append_section_from_file("Big", &std::fs::File::open(&src_path)?)?;
+        assert_eq!(data.len() as u64, entry.len());
+
+        writer
+            .section("Chorus")?
+            .write_all(b"Youth is running out, we finally feel it now\n")?;
+        let archive = writer.finish()?;
+
+        assert_eq!(
+            xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+            archive.checksum().into_u128(),
+        );
+
+        check_archive(&path, &data)?;
+        std::fs::remove_file(&path)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn import_from_file_fallback() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let data = big_data();
+    let src_path = dir.path().join("big");
+    std::fs::write(&src_path, &data)?;
+
+    // NOTE: Files opened for appending cannot be the destination of an in-kernel copy
+    let file = std::fs::OpenOptions::new()
+        .create_new(true)
+        .append(true)
+        .open(&path)?;
+
+    let mut writer = Writer::from_writer(BufWriter::new(file));
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.append_section_from_file("Big", &std::fs::File::open(&src_path)?)?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    check_archive(&path, &data)
+}
+
+#[test]
+pub fn import_from_reader() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let data = big_data();
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+
+    let mut extra = data.clone();
+    extra.extend_from_slice(b"not part of the section");
+    let entry = writer.append_section_from_reader("Big", &*extra, data.len() as u64)?;
+    assert_eq!(data.len() as u64, entry.len());
+
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    check_archive(&path, &data)
+}
+
+#[test]
+pub fn import_from_reader_too_short() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    let result = writer.append_section_from_reader("Verse 1", &b"Glazed eyes"[..], 100);
+
+    assert!(matches!(
+        result,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof,
+    ));
+
+    Ok(())
+}
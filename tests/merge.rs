@@ -0,0 +1,278 @@
+mod common;
+
+use common::{names, read_section, write_archive};
+use sfa::{ConflictPolicy, DropBehavior, Merger, Reader, Writer};
+use std::io::Write;
+
+fn setup(dir: &std::path::Path) -> Result<Merger, sfa::Error> {
+    let a = dir.join("a.sfa");
+    let b = dir.join("b.sfa");
+
+    write_archive(
+        &a,
+        &[
+            ("Verse 1", b"Glazed eyes and cherry pie\n"),
+            ("Chorus", b"Youth is running out\n"),
+        ],
+    )?;
+    write_archive(
+        &b,
+        &[
+            ("Chorus", b"We finally feel it now\n"),
+            ("Outro", b"There's a hush now in our hearts\n"),
+        ],
+    )?;
+
+    Merger::new().add_archive(&a)?.add_archive(&b)
+}
+
+#[test]
+pub fn merge_conflict_error() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("merged.sfa");
+
+    let result = setup(dir.path())?
+        .merge_into(Writer::new_at_path(&path)?.use_drop_behavior(DropBehavior::Delete));
+
+    assert!(matches!(
+        result,
+        Err(sfa::Error::DuplicateSectionName { path: Some(p), section })
+            if p == dir.path().join("b.sfa") && section == b"Chorus",
+    ));
+    assert!(!path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+pub fn merge_conflict_prefix() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("merged.sfa");
+
+    setup(dir.path())?
+        .use_conflict_policy(ConflictPolicy::Prefix)
+        .merge_into(Writer::new_at_path(&path)?)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert_eq!(
+        [
+            b"Verse 1".to_vec(),
+            b"a.sfa/Chorus".to_vec(),
+            b"b.sfa/Chorus".to_vec(),
+            b"Outro".to_vec(),
+        ],
+        *names(&reader),
+    );
+    assert_eq!(
+        b"Youth is running out\n",
+        &*read_section(&reader, &path, 1)?
+    );
+    assert_eq!(
+        b"We finally feel it now\n",
+        &*read_section(&reader, &path, 2)?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn merge_conflict_keep_last() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("merged.sfa");
+
+    let archive = setup(dir.path())?
+        .use_conflict_policy(ConflictPolicy::KeepLast)
+        .merge_into(Writer::new_at_path(&path)?.use_section_markers(true))?;
+    assert_eq!(3, archive.toc().len());
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert_eq!(
+        [b"Verse 1".to_vec(), b"Chorus".to_vec(), b"Outro".to_vec()],
+        *names(&reader),
+    );
+    assert_eq!(
+        b"We finally feel it now\n",
+        &*read_section(&reader, &path, 1)?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn merge_salvaged() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let a = dir.path().join("a.sfa");
+    let b = dir.path().join("b.sfa");
+    let path = dir.path().join("merged.sfa");
+
+    write_archive(&a, &[("Verse 1", b"Glazed eyes and cherry pie\n")])?;
+
+    {
+        let mut writer = Writer::new_at_path(&b)?
+            .use_section_markers(true)
+            .use_drop_behavior(DropBehavior::Keep);
+        writer
+            .section("Chorus")?
+            .write_all(b"Youth is running out\n")?;
+
+        // NOTE: The archive is dropped while the section is still open,
+        // so the section has no marker
+        let mut section = writer.section("Outro")?;
+        section.write_all(b"There's a hush now in our hearts\n")?;
+        std::mem::forget(section);
+    }
+
+    Merger::new()
+        .add_archive(&a)?
+        .add_reader(&b, Reader::salvage(&b)?)
+        .merge_into(Writer::new_at_path(&path)?)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+    assert_eq!([b"Verse 1".to_vec(), b"Chorus".to_vec()], *names(&reader));
+
+    Ok(())
+}
+
+#[test]
+pub fn merge_conflict_prefix_same_file_name() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("merged.sfa");
+
+    std::fs::create_dir(dir.path().join("a"))?;
+    std::fs::create_dir(dir.path().join("b"))?;
+    let a = dir.path().join("a").join("data.sfa");
+    let b = dir.path().join("b").join("data.sfa");
+    let c = dir.path().join("data.sfa~1");
+
+    write_archive(&a, &[("Chorus", b"Youth is running out\n")])?;
+    write_archive(&b, &[("Chorus", b"We finally feel it now\n")])?;
+
+    Merger::new()
+        .add_archive(&a)?
+        .add_archive(&b)?
+        .use_conflict_policy(ConflictPolicy::Prefix)
+        .merge_into(Writer::new_at_path(&path)?)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert_eq!(
+        [b"data.sfa~0/Chorus".to_vec(), b"data.sfa~1/Chorus".to_vec()],
+        *names(&reader),
+    );
+    assert_eq!(
+        b"We finally feel it now\n",
+        &*read_section(&reader, &path, 1)?
+    );
+
+    // The prefixed names conflict with a section of another archive
+    write_archive(
+        &c,
+        &[("Chorus", b"Hush\n"), ("data.sfa~1/Chorus", b"Hush\n")],
+    )?;
+
+    let result = Merger::new()
+        .add_archive(&a)?
+        .add_archive(&b)?
+        .add_archive(&c)?
+        .use_conflict_policy(ConflictPolicy::Prefix)
+        .merge_into(
+            Writer::new_at_path(dir.path().join("merged_2.sfa"))?
+                .use_drop_behavior(DropBehavior::Delete),
+        );
+
+    assert!(matches!(
+        result,
+        Err(sfa::Error::DuplicateSectionName { path: Some(p), section })
+            if p == c && section == b"data.sfa~1/Chorus",
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn merge_aliases() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let a = dir.path().join("a.sfa");
+    let b = dir.path().join("b.sfa");
+
+    let mut writer = Writer::new_at_path(&a)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.alias("Refrain", "Verse 1")?;
+    writer.alias_range("Eyes", "Verse 1", 7, 4)?;
+    writer.finish()?;
+
+    write_archive(&b, &[("Verse 1", b"We finally feel it now\n")])?;
+
+    // Aliases of copied sections stay aliases
+    let path = dir.path().join("merged.sfa");
+    Merger::new()
+        .add_archive(&b)?
+        .add_archive(&a)?
+        .use_conflict_policy(ConflictPolicy::Prefix)
+        .merge_into(Writer::new_at_path(&path)?)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert_eq!(
+        [
+            b"b.sfa/Verse 1".to_vec(),
+            b"a.sfa/Verse 1".to_vec(),
+            b"Refrain".to_vec(),
+            b"Eyes".to_vec(),
+        ],
+        *names(&reader),
+    );
+    assert_eq!(
+        [false, false, true, true],
+        *reader
+            .toc()
+            .iter()
+            .map(sfa::TocEntry::is_alias)
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(reader.toc()[1].pos(), reader.toc()[2].pos());
+    assert_eq!(reader.toc()[1].pos() + 7, reader.toc()[3].pos());
+    assert_eq!(
+        b"Glazed eyes and cherry pie\n",
+        &*read_section(&reader, &path, 2)?
+    );
+    assert_eq!(b"eyes", &*read_section(&reader, &path, 3)?);
+
+    // The aliased section is skipped, so the bytes of the first alias are copied,
+    // and the other alias lies within the copy
+    let path = dir.path().join("merged_2.sfa");
+    Merger::new()
+        .add_archive(&a)?
+        .add_archive(&b)?
+        .use_conflict_policy(ConflictPolicy::KeepLast)
+        .merge_into(Writer::new_at_path(&path)?)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert_eq!(
+        [b"Refrain".to_vec(), b"Eyes".to_vec(), b"Verse 1".to_vec()],
+        *names(&reader),
+    );
+    assert_eq!(
+        [false, true, false],
+        *reader
+            .toc()
+            .iter()
+            .map(sfa::TocEntry::is_alias)
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(reader.toc()[0].pos() + 7, reader.toc()[1].pos());
+    assert_eq!(b"eyes", &*read_section(&reader, &path, 1)?);
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::{fix_trailer_checksum, read_section, TRAILER_V3_SIZE};
+use sfa::{verify_proof, InclusionProof, Reader, Writer};
+use std::io::Write;
+
+fn write_archive(
+    path: &std::path::Path,
+    sections: usize,
+    toc_mirror: bool,
+) -> Result<sfa::FinishedArchive, sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?
+        .use_merkle_root(true)
+        .use_toc_mirror(toc_mirror)
+        .use_block_checksums(16);
+
+    for idx in 0..sections {
+        writer
+            .section(format!("Section {idx}"))?
+            .write_all(format!("Glazed eyes and cherry pie {idx}\n").as_bytes())?;
+    }
+
+    writer.finish()
+}
+
+#[test]
+pub fn merkle_inclusion_proof() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for sections in 0..=9 {
+        for toc_mirror in [false, true] {
+            let path = dir
+                .path()
+                .join(format!("cherry_pie_{sections}_{toc_mirror}"));
+            let archive = write_archive(&path, sections, toc_mirror)?;
+            let root = archive.merkle_root().unwrap();
+
+            let reader = Reader::new(&path)?;
+            reader.verify(&path)?;
+            assert!(Reader::validate(&path)?.is_valid());
+            assert_eq!(Some(root), reader.merkle_root());
+
+            for idx in 0..sections {
+                let name = format!("Section {idx}");
+
+                // NOTE: The proof is sent to the client along with the section contents
+                let proof = InclusionProof::from_bytes(
+                    &reader.inclusion_proof(name.as_bytes()).unwrap().to_bytes(),
+                )?;
+                assert_eq!(name.as_bytes(), proof.entry().name());
+
+                let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+                hasher.update(&read_section(&reader, &path, idx)?);
+                let digest = proof.entry().checksum().unwrap();
+                assert_eq!(hasher.digest128(), digest.into_u128());
+
+                assert!(verify_proof(root, proof.entry(), digest, &proof));
+
+                // Another section's checksum
+                let other = reader.toc()[(idx + 1) % sections].checksum().unwrap();
+                assert_eq!(
+                    sections == 1,
+                    verify_proof(root, proof.entry(), other, &proof),
+                );
+
+                // Another section's entry
+                let other = &reader.toc()[(idx + 1) % sections];
+                assert_eq!(sections == 1, verify_proof(root, other, digest, &proof));
+            }
+
+            assert!(reader.inclusion_proof(b"Chorus").is_none());
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn merkle_root_disabled() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    assert!(writer.finish()?.merkle_root().is_none());
+
+    let reader = Reader::new(&path)?;
+    assert!(reader.merkle_root().is_none());
+    assert!(reader.inclusion_proof(b"Verse 1").is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn merkle_root_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, 3, false)?;
+
+    // Replace the Merkle root and fix up the trailer checksum
+    let mut bytes = std::fs::read(&path)?;
+    let trailer_pos = bytes.len() - TRAILER_V3_SIZE;
+    let trailer = &mut bytes[trailer_pos..];
+    trailer[50] ^= 1;
+
+    fix_trailer_checksum(trailer);
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::MerkleRootMismatch { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn merkle_proof_truncated() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, 3, false)?;
+
+    let reader = Reader::new(&path)?;
+    let bytes = reader.inclusion_proof(b"Section 1").unwrap().to_bytes();
+
+    assert!(InclusionProof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(InclusionProof::from_bytes(&bytes[1..]).is_err());
+
+    Ok(())
+}
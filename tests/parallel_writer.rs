@@ -0,0 +1,134 @@
+mod common;
+
+use common::{names, read_section};
+use sfa::{ParallelWriter, Reader, SectionOrder, Writer};
+use std::io::Write;
+
+#[test]
+pub fn parallel_writer_threads() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let writer = ParallelWriter::new(Writer::new_at_path(&path)?.use_section_markers(true))
+        .use_spill_threshold(1_000);
+
+    std::thread::scope(|scope| {
+        let workers = (0..16u32)
+            .map(|idx| {
+                let mut section = writer.section(format!("Section {idx}"));
+
+                scope.spawn(move || {
+                    for _ in 0..idx * 10 {
+                        section.write_all(&idx.to_le_bytes())?;
+                    }
+                    section.finish()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for worker in workers {
+            worker.join().unwrap()?;
+        }
+
+        Ok::<(), std::io::Error>(())
+    })?;
+
+    let archive = writer.finish()?;
+    assert_eq!(
+        xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+        archive.checksum().into_u128(),
+    );
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+    assert_eq!(16, reader.toc().len());
+
+    for idx in 0..16u32 {
+        assert_eq!(
+            format!("Section {idx}").as_bytes(),
+            reader.toc()[idx as usize].name()
+        );
+        assert_eq!(
+            idx.to_le_bytes().repeat(idx as usize * 10),
+            read_section(&reader, &path, idx as usize)?,
+        );
+    }
+
+    // NOTE: Spill files are removed once the sections are written
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+pub fn parallel_writer_order() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for (order, expected) in [
+        (
+            SectionOrder::Deterministic,
+            [b"Verse 1".to_vec(), b"Chorus".to_vec(), b"Outro".to_vec()],
+        ),
+        (
+            SectionOrder::Completion,
+            [b"Outro".to_vec(), b"Chorus".to_vec(), b"Verse 1".to_vec()],
+        ),
+    ] {
+        let path = dir.path().join(format!("cherry_pie_{order:?}"));
+
+        let writer = ParallelWriter::new(Writer::new_at_path(&path)?).use_order(order);
+
+        let mut verse = writer.section("Verse 1");
+        let mut chorus = writer.section("Chorus");
+        let mut outro = writer.section("Outro");
+
+        verse.write_all(b"Glazed eyes and cherry pie\n")?;
+        chorus.write_all(b"Youth is running out, we finally feel it now\n")?;
+        outro.write_all(b"There's a hush now in our hearts\n")?;
+
+        outro.finish()?;
+        chorus.finish()?;
+        verse.finish()?;
+
+        writer.finish()?;
+
+        let reader = Reader::new(&path)?;
+        reader.verify(&path)?;
+        assert_eq!(expected, *names(&reader));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn parallel_writer_dropped_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let writer = ParallelWriter::new(Writer::new_at_path(&path)?).use_spill_threshold(0);
+
+    let mut verse = writer.section("Verse 1");
+    let mut chorus = writer.section("Chorus");
+    let mut outro = writer.section("Outro");
+
+    verse.write_all(b"Glazed eyes and cherry pie\n")?;
+    chorus.write_all(b"Youth is running out, we finally feel it now\n")?;
+    outro.write_all(b"There's a hush now in our hearts\n")?;
+
+    outro.finish()?;
+    verse.finish()?;
+    drop(chorus);
+
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+    assert_eq!([b"Verse 1".to_vec(), b"Outro".to_vec()], *names(&reader));
+    assert_eq!(
+        b"There's a hush now in our hearts\n",
+        &*read_section(&reader, &path, 1)?,
+    );
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
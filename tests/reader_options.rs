@@ -0,0 +1,132 @@
+mod common;
+
+use common::{write_cherry_pie, TRAILER_CHECKSUMMED_SIZE, TRAILER_SIZE};
+use sfa::{Reader, ReaderOptions};
+
+#[test]
+pub fn reader_options_within_limits() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default()
+        .max_toc_size(1_000)
+        .max_entry_count(2)
+        .max_name_len(7);
+
+    let reader = Reader::with_options(&path, &options)?;
+    assert_eq!(2, reader.toc().len());
+
+    Ok(())
+}
+
+#[test]
+pub fn reader_options_toc_too_large() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default().max_toc_size(10);
+
+    assert!(matches!(
+        Reader::with_options(&path, &options),
+        Err(sfa::Error::TocTooLarge { limit: 10, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn reader_options_too_many_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default().max_entry_count(1);
+
+    assert!(matches!(
+        Reader::with_options(&path, &options),
+        Err(sfa::Error::TooManySections {
+            count: 2,
+            limit: 1,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn reader_options_section_name_too_long() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default().max_name_len(6);
+
+    assert!(matches!(
+        Reader::with_options(&path, &options),
+        Err(sfa::Error::SectionNameTooLong {
+            len: 7,
+            limit: 6,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn reader_options_hostile_entry_count() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("hostile");
+
+    // A ToC that claims to contain u32::MAX entries
+    let mut toc = vec![];
+    toc.extend_from_slice(b"TOC!");
+    toc.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut trailer = vec![];
+    trailer.extend_from_slice(&xxhash_rust::xxh3::xxh3_128(&toc).to_le_bytes());
+    trailer.extend_from_slice(&[0; 16]);
+    trailer.extend_from_slice(&0u64.to_le_bytes());
+    trailer.extend_from_slice(&(toc.len() as u64).to_le_bytes());
+    trailer.push(0); // flags
+    trailer.push(0); // checksum type
+    let trailer_checksum = xxhash_rust::xxh3::xxh3_64(&trailer);
+    trailer.extend_from_slice(&trailer_checksum.to_le_bytes());
+    trailer.push(0x2);
+    trailer.extend_from_slice(b"SFA!");
+    assert_eq!(TRAILER_CHECKSUMMED_SIZE + 8 + 1 + 4, trailer.len());
+    assert_eq!(TRAILER_SIZE, trailer.len());
+
+    std::fs::write(&path, [toc, trailer].concat())?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TruncatedToc { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn reader_options_validate() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default().max_entry_count(2);
+    assert!(Reader::validate_with_options(&path, &options)?.is_valid());
+
+    assert!(matches!(
+        Reader::validate_with_options(&path, &ReaderOptions::default().max_toc_size(10)),
+        Err(sfa::Error::TocTooLarge { limit: 10, .. })
+    ));
+    assert!(matches!(
+        Reader::validate_with_options(&path, &ReaderOptions::default().max_entry_count(1)),
+        Err(sfa::Error::TooManySections { limit: 1, .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,417 @@
+mod common;
+
+use common::data;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+const SHARD_SIZE: u32 = 256;
+
+fn write_archive(
+    path: &std::path::Path,
+    block_size: u32,
+    section_markers: bool,
+    toc_mirror: bool,
+) -> Result<sfa::FinishedArchive, sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?
+        .use_parity(SHARD_SIZE, 4, 2)
+        .use_block_checksums(block_size)
+        .use_section_markers(section_markers)
+        .use_toc_mirror(toc_mirror)
+        .use_dedup(true);
+
+    writer
+        .section("small")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("large")?.write_all(&data(10_500))?;
+    writer.section("empty")?.finish()?;
+    writer.section("copy")?.write_all(&data(10_500))?;
+    writer.alias_range("range", "large", 10, 20)?;
+    writer.section("tail")?.write_all(&data(3_000))?;
+
+    writer.finish()
+}
+
+fn corrupt(path: &std::path::Path, positions: &[u64]) -> std::io::Result<()> {
+    let mut bytes = std::fs::read(path)?;
+    for pos in positions {
+        bytes[*pos as usize] ^= 0xff;
+    }
+    std::fs::write(path, &bytes)
+}
+
+#[test]
+pub fn repair_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for block_size in [0, SHARD_SIZE, 100] {
+        for (section_markers, toc_mirror) in [(false, false), (true, false), (false, true)] {
+            let path = dir.path().join(format!(
+                "cherry_pie_{block_size}_{section_markers}_{toc_mirror}"
+            ));
+            write_archive(&path, block_size, section_markers, toc_mirror)?;
+
+            let reader = Reader::new(&path)?;
+            reader.verify(&path)?;
+            assert!(Reader::validate(&path)?.is_valid());
+
+            let report = reader.repair(&path)?;
+            assert!(report.regions().is_empty());
+            assert!(report.is_repaired());
+
+            // NOTE: Without block checksums, the damaged shard of the large section
+            // (which spans multiple stripes) is located using the parity
+            let (section, pos) = (&b"large"[..], reader.toc()[1].pos() + 5_000);
+
+            corrupt(&path, &[pos])?;
+            assert!(reader.verify(&path).is_err());
+
+            let report = reader.repair(&path)?;
+            assert_eq!(1, report.regions().len());
+            assert!(report.is_repaired());
+
+            let region = &report.regions()[0];
+            assert_eq!(Some(section), region.section());
+            assert!(region.pos() <= pos);
+            assert!(region.pos() + region.len() > pos);
+
+            reader.verify(&path)?;
+            assert!(Reader::validate(&path)?.is_valid());
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_multiple_shards() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, SHARD_SIZE, false, false)?;
+
+    let reader = Reader::new(&path)?;
+    let large = reader.toc()[1].pos();
+    let tail = reader.toc()[5].pos();
+
+    // NOTE: Each damaged block overlaps two shards, because the sections are not aligned to shards
+    let block = u64::from(SHARD_SIZE);
+    corrupt(
+        &path,
+        &[
+            large + 4 * block,
+            large + 12 * block,
+            large + 30 * block,
+            tail + 100,
+        ],
+    )?;
+
+    let report = reader.repair(&path)?;
+    assert_eq!(4, report.regions().len());
+    assert!(report.is_repaired());
+
+    reader.verify(&path)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_without_block_checksums() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, 0, false, false)?;
+    let checksum = archive.checksum();
+
+    let reader = Reader::new(&path)?;
+    let large = reader.toc()[1].pos();
+    let copy = reader.toc()[3].pos();
+
+    // Damage shards in different stripes of the same section, and two sections at once
+    let shard = u64::from(SHARD_SIZE);
+    corrupt(
+        &path,
+        &[
+            large + 10,
+            large + 10 * shard,
+            large + 30 * shard,
+            copy + 100,
+        ],
+    )?;
+
+    let report = reader.repair(&path)?;
+    assert_eq!(2, report.regions().len());
+    assert!(report.is_repaired());
+
+    assert_eq!(
+        xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+        checksum.into_u128(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_too_many_shards() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, SHARD_SIZE, false, false)?;
+
+    let reader = Reader::new(&path)?;
+    let large = reader.toc()[1].pos();
+    let tail = reader.toc()[5].pos();
+
+    // NOTE: Three shards of the same stripe cannot be repaired using two parity shards,
+    // but other stripes can still be repaired (without section markers, the data stream
+    // starts at the beginning of the file, so the stripes start at multiples of the stripe size)
+    let shard = u64::from(SHARD_SIZE);
+    let stripe = large.div_ceil(4 * shard) * 4 * shard;
+    corrupt(
+        &path,
+        &[
+            stripe + 10,
+            stripe + shard + 10,
+            stripe + 2 * shard + 10,
+            tail + 100,
+        ],
+    )?;
+
+    let report = reader.repair(&path)?;
+    assert!(!report.is_repaired());
+
+    let (repaired, damaged): (Vec<_>, Vec<_>) = report
+        .regions()
+        .iter()
+        .partition(|region| region.is_repaired());
+
+    assert_eq!(3, damaged.len());
+    assert!(damaged
+        .iter()
+        .all(|region| region.section() == Some(&b"large"[..])));
+    assert!(repaired
+        .iter()
+        .any(|region| region.section() == Some(&b"tail"[..])));
+
+    assert!(reader.verify(&path).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_all_combinations() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    // NOTE: A single section starting at 0, so its blocks are aligned to the shards
+    let mut writer = Writer::new_at_path(&path)?
+        .use_parity(SHARD_SIZE, 5, 3)
+        .use_block_checksums(SHARD_SIZE);
+    writer
+        .section("Verse 1")?
+        .write_all(&data(12 * SHARD_SIZE as usize))?;
+    let checksum = writer.finish()?.checksum();
+
+    let reader = Reader::new(&path)?;
+    let shard = u64::from(SHARD_SIZE);
+    let parity_pos = reader.toc()[0].len();
+
+    // Data shards and parity shards of the first stripe
+    let shards = (0..5)
+        .map(|idx| idx * shard)
+        .chain((0..3).map(|idx| parity_pos + idx * shard))
+        .collect::<Vec<_>>();
+
+    for mask in 1u32..(1 << shards.len()) {
+        if mask.count_ones() > 3 {
+            continue;
+        }
+
+        let positions = shards
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| mask & (1 << idx) != 0)
+            .map(|(_, pos)| pos + 7)
+            .collect::<Vec<_>>();
+
+        corrupt(&path, &positions)?;
+
+        let report = reader.repair(&path)?;
+        assert_eq!(mask.count_ones() as usize, report.regions().len());
+        assert!(report.is_repaired());
+
+        assert_eq!(
+            xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+            checksum.into_u128(),
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_parity_shard() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, 0, false, false)?;
+    let checksum = archive.checksum();
+
+    let reader = Reader::new(&path)?;
+    let data_end = reader
+        .toc()
+        .iter()
+        .map(|entry| entry.pos() + entry.len())
+        .max()
+        .unwrap();
+
+    // Damage a parity shard and the section before it
+    corrupt(&path, &[data_end + 10, data_end - 10])?;
+
+    let report = reader.repair(&path)?;
+    assert_eq!(2, report.regions().len());
+    assert!(report.is_repaired());
+    assert_eq!(Some(&b"tail"[..]), report.regions()[0].section());
+    assert_eq!(None, report.regions()[1].section());
+
+    assert_eq!(
+        xxhash_rust::xxh3::xxh3_128(&std::fs::read(&path)?),
+        checksum.into_u128(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_without_parity() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    corrupt(&path, &[3])?;
+
+    let reader = Reader::new(&path)?;
+    let report = reader.repair(&path)?;
+    assert_eq!(1, report.regions().len());
+    assert!(!report.is_repaired());
+    assert_eq!(Some(&b"Verse 1"[..]), report.regions()[0].section());
+    assert_eq!(
+        (0, 27),
+        (report.regions()[0].pos(), report.regions()[0].len())
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_invalid_parity_layout() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for (idx, (shard_size, data_shards, parity_shards)) in [
+        (0, 4, 2),
+        (SHARD_SIZE, 0, 0),
+        (SHARD_SIZE, 0, 2),
+        (SHARD_SIZE, 4, 0),
+        (SHARD_SIZE, 200, 100),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let path = dir.path().join(format!("cherry_pie_{idx}"));
+
+        let mut writer =
+            Writer::new_at_path(&path)?.use_parity(shard_size, data_shards, parity_shards);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+
+        assert!(matches!(
+            writer.finish(),
+            Err(sfa::Error::InvalidParityLayout {
+                path: Some(err_path),
+                shard_size: got_shard_size,
+                data_shards: got_data_shards,
+                parity_shards: got_parity_shards,
+            }) if err_path == path
+                && (got_shard_size, got_data_shards, got_parity_shards)
+                    == (shard_size, data_shards, parity_shards),
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn repair_rewrite() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let dest = dir.path().join("cherry_pie_rewritten");
+    write_archive(&path, 0, false, false)?;
+
+    Reader::new(&path)?.rewrite(&path, &dest)?;
+
+    let reader = Reader::new(&dest)?;
+    assert!(Reader::validate(&dest)?.is_valid());
+
+    corrupt(&dest, &[reader.toc()[0].pos() + 1])?;
+    assert!(reader.repair(&dest)?.is_repaired());
+    reader.verify(&dest)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn parity_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, 0, false, false)?;
+
+    // Corrupt the data length in the parity footer, which is located before the table of contents
+    corrupt(&path, &[archive.toc_pos() - 16])?;
+
+    // NOTE: The parity region is not needed to read the archive
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    assert!(matches!(
+        reader.repair(&path),
+        Err(sfa::Error::InvalidParity { .. }),
+    ));
+    assert!(Reader::validate(&path)?
+        .issues()
+        .contains(&sfa::ValidationIssue::InvalidParity));
+
+    Ok(())
+}
+
+#[test]
+pub fn parity_forged() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let archive = write_archive(&path, 0, false, false)?;
+
+    // Replace the checksum of the last parity shard, and fix up the checksum in the parity footer,
+    // which does not help, because the parity region digest is stored in the trailer
+    let mut bytes = std::fs::read(&path)?;
+    let footer = archive.toc_pos() as usize - 26;
+    let data_len = u64::from_le_bytes(bytes[footer + 6..footer + 14].try_into().unwrap());
+    let shard_count = data_len.div_ceil(u64::from(SHARD_SIZE)).div_ceil(4) as usize * 2;
+    let checksums = footer - shard_count * 16;
+
+    bytes[footer - 1] ^= 1;
+    let checksum = xxhash_rust::xxh3::xxh3_64(&bytes[checksums..footer + 14]);
+    bytes[footer + 14..footer + 22].copy_from_slice(&checksum.to_le_bytes());
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::new(&path)?;
+
+    assert!(matches!(
+        reader.repair(&path),
+        Err(sfa::Error::InvalidParity { .. }),
+    ));
+    assert!(Reader::validate(&path)?
+        .issues()
+        .contains(&sfa::ValidationIssue::InvalidParity));
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use common::{names, read_section};
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn salvage_unfinished() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    {
+        let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+        writer
+            .section("Verse 1")?
+            .write_all(b"Glazed eyes and cherry pie\n")?;
+        writer.section("Verse 2")?.finish()?;
+        writer
+            .section("Chorus")?
+            .write_all(b"Youth is running out, we finally feel it now\n")?;
+
+        // Writer is dropped without finishing, while the section is still open
+        let mut section = writer.section("Outro")?;
+        section.write_all(b"There's a hush now in our hearts\n")?;
+        std::mem::forget(section);
+    }
+
+    assert!(Reader::new(&path).is_err());
+
+    let reader = Reader::salvage(&path)?;
+    {
+        let toc = reader.toc();
+        assert_eq!(3, toc.len());
+
+        assert_eq!(b"Verse 1", toc[0].name());
+        assert_eq!(
+            read_section(&reader, &path, 0)?,
+            b"Glazed eyes and cherry pie\n"
+        );
+
+        assert_eq!(b"Verse 2", toc[1].name());
+        assert_eq!(read_section(&reader, &path, 1)?, b"");
+
+        assert_eq!(b"Chorus", toc[2].name());
+        assert_eq!(
+            read_section(&reader, &path, 2)?,
+            b"Youth is running out, we finally feel it now\n"
+        );
+    }
+
+    let new_path = dir.path().join("cherry_pie_rewritten");
+    reader.rewrite(&path, &new_path)?;
+
+    let reader = Reader::new(&new_path)?;
+    reader.verify(&new_path)?;
+    assert_eq!(3, reader.toc().len());
+    assert_eq!(b"Verse 1", reader.toc()[0].name());
+    assert_eq!(b"Verse 2", reader.toc()[1].name());
+    assert_eq!(b"Chorus", reader.toc()[2].name());
+    assert_eq!(
+        read_section(&reader, &new_path, 2)?,
+        b"Youth is running out, we finally feel it now\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn salvage_corrupted_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer
+        .section("Outro")?
+        .write_all(b"There's a hush now in our hearts\n")?;
+    writer.finish()?;
+
+    let chorus_pos = Reader::new(&path)?.toc()[1].pos();
+
+    // Corrupt "Chorus" and chop off the trailer
+    let mut bytes = std::fs::read(&path)?;
+    bytes[chorus_pos as usize] = b'y';
+    bytes.truncate(bytes.len() - 10);
+    std::fs::write(&path, &bytes)?;
+
+    assert!(Reader::new(&path).is_err());
+
+    let reader = Reader::salvage(&path)?;
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(b"Outro", toc[1].name());
+    assert_eq!(
+        read_section(&reader, &path, 1)?,
+        b"There's a hush now in our hearts\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn salvage_corrupted_marker() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+    for (name, data) in [
+        ("s1", &b"Glazed eyes and cherry pie\n"[..]),
+        ("s2", b"Youth is running out, we finally feel it now\n"),
+        ("s3", b"There's a hush now in our hearts\n"),
+        ("s4", b"There's a hush now, it glows dark\n"),
+    ] {
+        writer.section(name)?.write_all(data)?;
+    }
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    let s2_end = (reader.toc()[1].pos() + reader.toc()[1].len()) as usize;
+
+    // Rename "s2" to "t2" in its marker (after the magic, checksum type, pos, len and name length),
+    // and chop off the trailer
+    let mut bytes = std::fs::read(&path)?;
+    assert_eq!(b"SEC!", &bytes[s2_end..s2_end + 4]);
+    bytes[s2_end + 4 + 1 + 8 + 8 + 2] = b't';
+    bytes.truncate(bytes.len() - 10);
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::salvage(&path)?;
+    assert_eq!(
+        [b"s1".to_vec(), b"s3".to_vec(), b"s4".to_vec()],
+        *names(&reader)
+    );
+    assert_eq!(
+        read_section(&reader, &path, 2)?,
+        b"There's a hush now, it glows dark\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn salvage_finished() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+    writer.section("")?.write_all(b"SEC!")?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\nSEC!")?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let salvaged = Reader::salvage(&path)?;
+    assert_eq!(3, salvaged.toc().len());
+
+    for (a, b) in reader.toc().iter().zip(salvaged.toc().iter()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.pos(), b.pos());
+        assert_eq!(a.len(), b.len());
+        assert_eq!(a.checksum(), b.checksum());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn salvage_without_markers() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    let reader = Reader::salvage(&path)?;
+    assert!(reader.toc().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn section_checksum() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("Verse 2")?.finish()?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(
+        Some(xxhash_rust::xxh3::xxh3_128(b"Glazed eyes and cherry pie\n")),
+        toc[0].checksum().map(sfa::Checksum::into_u128),
+    );
+    assert_eq!(
+        Some(xxhash_rust::xxh3::xxh3_128(b"")),
+        toc[1].checksum().map(sfa::Checksum::into_u128),
+    );
+
+    // Corrupt "Chorus"
+    let mut bytes = std::fs::read(&path)?;
+    bytes[toc[2].pos() as usize] = b'y';
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::new(&path)?;
+    assert!(matches!(
+        reader.verify(&path),
+        Err(sfa::Error::ChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn section_checksum_v1() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("v1");
+
+    let mut toc = vec![];
+    toc.extend_from_slice(b"TOC!");
+    toc.extend_from_slice(&1u32.to_le_bytes());
+    toc.extend_from_slice(&0u64.to_le_bytes());
+    toc.extend_from_slice(&5u64.to_le_bytes());
+    toc.extend_from_slice(&5u16.to_le_bytes());
+    toc.extend_from_slice(b"Hello");
+
+    let mut bytes = b"World".to_vec();
+    bytes.extend_from_slice(&toc);
+    bytes.extend_from_slice(b"SFA!");
+    bytes.push(0x1);
+    bytes.push(0x0);
+    bytes.extend_from_slice(&xxhash_rust::xxh3::xxh3_128(&toc).to_le_bytes());
+    bytes.extend_from_slice(&5u64.to_le_bytes());
+    bytes.extend_from_slice(&(toc.len() as u64).to_le_bytes());
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(1, toc.len());
+    assert_eq!(b"Hello", toc[0].name());
+    assert_eq!(5, toc[0].len());
+    assert_eq!(None, toc[0].checksum());
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::read_section;
+use sfa::{Reader, Writer};
+use std::io::Write;
+
+#[test]
+pub fn section_writer() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?.use_section_markers(true);
+
+    let mut section = writer.section("Verse 1")?;
+    section.write_all(b"Glazed eyes and cherry pie\n")?;
+    let verse = section.finish()?;
+    assert_eq!(b"Verse 1", verse.name());
+    assert_eq!(0, verse.pos());
+    assert_eq!(27, verse.len());
+
+    {
+        let mut section = writer.section("Chorus")?;
+        section.write_all(b"Youth is running out, we finally feel it now\n")?;
+    }
+
+    let empty = writer.section("Empty")?.finish()?;
+    assert_eq!(0, empty.len());
+
+    let archive = writer.finish()?;
+    assert_eq!(3, archive.toc().len());
+    assert_eq!(verse.checksum(), archive.toc()[0].checksum());
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(3, toc.len());
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(b"Chorus", toc[1].name());
+    assert_eq!(b"Empty", toc[2].name());
+
+    assert_eq!(
+        b"Glazed eyes and cherry pie\n",
+        &*read_section(&reader, &path, 0)?,
+    );
+    assert_eq!(
+        b"Youth is running out, we finally feel it now\n",
+        &*read_section(&reader, &path, 1)?,
+    );
+    assert!(read_section(&reader, &path, 2)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+#![cfg(feature = "signing")]
+
+mod common;
+
+use common::{fix_trailer_checksum, TRAILER_CHECKSUMMED_SIZE, TRAILER_SIZE, TRAILER_V3_SIZE};
+use sfa::{ChecksumType, Reader, SigningKey, Writer};
+use std::io::Write;
+
+fn write_archive(path: &std::path::Path, key: Option<&SigningKey>) -> Result<(), sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?.use_checksum_type(ChecksumType::Sha256);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+
+    match key {
+        Some(key) => writer.finish_signed(key)?,
+        None => writer.finish()?,
+    };
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_roundtrip() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let other_key = SigningKey::from_bytes(&[2; 32]);
+    write_archive(&path, Some(&key))?;
+
+    let reader = Reader::new_verified(&path, &[other_key.verifying_key(), key.verifying_key()])?;
+    assert_eq!(2, reader.toc().len());
+    assert_eq!(ChecksumType::Sha256, reader.checksum_type());
+    reader.verify(&path)?;
+
+    // Signature is ignored by default
+    let reader = Reader::new(&path)?;
+    assert_eq!(2, reader.toc().len());
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_unsigned() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("unsigned");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+    write_archive(&path, None)?;
+
+    assert!(matches!(
+        Reader::new_verified(&path, &[key.verifying_key()]),
+        Err(sfa::Error::MissingSignature { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_wrong_key() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let other_key = SigningKey::from_bytes(&[2; 32]);
+    write_archive(&path, Some(&key))?;
+
+    assert!(matches!(
+        Reader::new_verified(&path, &[other_key.verifying_key()]),
+        Err(sfa::Error::InvalidSignature { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_tampered_toc() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+    write_archive(&path, Some(&key))?;
+
+    // Rename "Chorus" to "Chorux"
+    let mut bytes = std::fs::read(&path)?;
+    let idx = bytes
+        .windows(6)
+        .rposition(|window| window == b"Chorus")
+        .expect("section name should exist");
+    bytes[idx + 5] = b'x';
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new_verified(&path, &[key.verifying_key()]),
+        Err(sfa::Error::InvalidSignature { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_tampered_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+    write_archive(&path, Some(&key))?;
+
+    let mut bytes = std::fs::read(&path)?;
+    bytes[0] = b'B';
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::new_verified(&path, &[key.verifying_key()])?;
+    assert!(matches!(
+        reader.verify(&path),
+        Err(sfa::Error::ChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_merkle_root() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+
+    let mut writer = Writer::new_at_path(&path)?
+        .use_checksum_type(ChecksumType::Sha256)
+        .use_merkle_root(true);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    let archive = writer.finish_signed(&key)?;
+
+    let reader = Reader::new_verified(&path, &[key.verifying_key()])?;
+    assert_eq!(archive.merkle_root(), reader.merkle_root());
+    assert!(Reader::validate(&path)?.is_valid());
+
+    // Flip a bit in the Merkle root
+    let mut bytes = std::fs::read(&path)?;
+    let idx = bytes.len() - TRAILER_V3_SIZE + TRAILER_CHECKSUMMED_SIZE;
+    bytes[idx] ^= 1;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(Reader::new_verified(&path, &[key.verifying_key()]).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_insecure_checksum_type() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+
+    assert!(matches!(
+        writer.finish_signed(&key),
+        Err(sfa::Error::InsecureChecksumType {
+            checksum_type: ChecksumType::Xxh3,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_insecure_checksum_type_read() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("signed");
+
+    let key = SigningKey::from_bytes(&[1; 32]);
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+
+    // Mark the XXH3 archive as signed, and append a (bogus) signature before the trailer
+    let mut bytes = std::fs::read(&path)?;
+    let mut trailer = bytes.split_off(bytes.len() - TRAILER_SIZE);
+    trailer[48] |= 0x1;
+    fix_trailer_checksum(&mut trailer);
+    bytes.extend_from_slice(&[0; 64]);
+    bytes.extend_from_slice(&trailer);
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new_verified(&path, &[key.verifying_key()]),
+        Err(sfa::Error::InsecureChecksumType {
+            checksum_type: ChecksumType::Xxh3,
+            ..
+        })
+    ));
+
+    Ok(())
+}
@@ -8,49 +8,54 @@ pub fn cherry_pie() -> Result<(), sfa::Error> {
     // let path = std::path::Path::new("test_fixture/cherry_pie_broken");
 
     let mut writer = Writer::new_at_path(&path)?;
-    writer.start("Verse 1")?;
-    writer.write_all(b"Glazed eyes and cherry pie\n")?;
-    writer.write_all(b"We are high spirits in those L.A. skies\n")?;
-    writer.write_all(b"Griffith Lookout, look out\n")?;
-    writer.write_all(b"The gates will shut and the sky will glow dark\n")?;
-    writer.write_all(b"Can you see it changing?\n")?;
-    writer.start("Chorus")?;
-    writer.write_all(b"Youth is running out, we finally feel it now\n")?;
-    writer
+    let mut section = writer.section("Verse 1")?;
+    section.write_all(b"Glazed eyes and cherry pie\n")?;
+    section.write_all(b"We are high spirits in those L.A. skies\n")?;
+    section.write_all(b"Griffith Lookout, look out\n")?;
+    section.write_all(b"The gates will shut and the sky will glow dark\n")?;
+    section.write_all(b"Can you see it changing?\n")?;
+    section.finish()?;
+    let mut section = writer.section("Chorus")?;
+    section.write_all(b"Youth is running out, we finally feel it now\n")?;
+    section
         .write_all(b"The years from here get faster as the lights keep blurring past this car\n")?;
-    writer.write_all(b"My mind is changing, forever is fading\n")?;
-    writer.write_all(b"So we search for something else\n")?;
-    writer.write_all(b"And as we watch the sun go down\n")?;
-    writer.write_all(b"Everything that we knew then we don't know now\n")?;
-    writer.write_all(b"There's a silence breathing through the car\n")?;
-    writer.write_all(b"My mind is changing, forever is fading\n")?;
-    writer.write_all(b"So we search for something else\n")?;
-    writer.start("Verse 2")?;
-    writer.write_all(b"Your phone glow face in the dark\n")?;
-    writer.write_all(b"Lights up the living room and our whispers start\n")?;
-    writer.write_all(b"Can you sleep well? Nor can I\n")?;
-    writer.write_all(b"Should we just talk all night and compare our minds?\n")?;
-    writer.write_all(b"Do you feel the same thing?\n")?;
-    writer.start("Chorus 2")?;
-    writer.write_all(b"Youth is running out, we finally feel it now\n")?;
-    writer
+    section.write_all(b"My mind is changing, forever is fading\n")?;
+    section.write_all(b"So we search for something else\n")?;
+    section.write_all(b"And as we watch the sun go down\n")?;
+    section.write_all(b"Everything that we knew then we don't know now\n")?;
+    section.write_all(b"There's a silence breathing through the car\n")?;
+    section.write_all(b"My mind is changing, forever is fading\n")?;
+    section.write_all(b"So we search for something else\n")?;
+    section.finish()?;
+    let mut section = writer.section("Verse 2")?;
+    section.write_all(b"Your phone glow face in the dark\n")?;
+    section.write_all(b"Lights up the living room and our whispers start\n")?;
+    section.write_all(b"Can you sleep well? Nor can I\n")?;
+    section.write_all(b"Should we just talk all night and compare our minds?\n")?;
+    section.write_all(b"Do you feel the same thing?\n")?;
+    section.finish()?;
+    let mut section = writer.section("Chorus 2")?;
+    section.write_all(b"Youth is running out, we finally feel it now\n")?;
+    section
         .write_all(b"The years from here get faster as the lights keep blurring past this car\n")?;
-    writer.write_all(b"My mind is changing, forever is fading\n")?;
-    writer.write_all(b"So we search for something else\n")?;
-    writer.write_all(b"And as we watch the sun go down\n")?;
-    writer.write_all(b"Everything that we knew then we don't know now\n")?;
-    writer.write_all(b"There's a silence breathing through the car\n")?;
-    writer.write_all(b"My mind is changing, forever is fading\n")?;
-    writer.write_all(b"So we search for something else\n")?;
-    writer.start("Outro")?;
-    writer.write_all(b"There's a hush now in our hearts\n")?;
-    writer.write_all(b"There's a hush now, it glows dark\n")?;
-    writer.write_all(b"There's a hush now in our hearts\n")?;
-    writer.write_all(b"There's a hush now, it glows dark (Do you feel the same thing?)\n")?;
-    writer.write_all(b"There's a hush now in our hearts (All the lights keep blurring)\n")?;
-    writer.write_all(b"There's a hush now, it glows dark (There's a silence breathing)\n")?;
-    writer.write_all(b"There's a hush now in our hearts (Can you see it changing?)\n")?;
-    writer.write_all(b"There's a hush now\n")?;
+    section.write_all(b"My mind is changing, forever is fading\n")?;
+    section.write_all(b"So we search for something else\n")?;
+    section.write_all(b"And as we watch the sun go down\n")?;
+    section.write_all(b"Everything that we knew then we don't know now\n")?;
+    section.write_all(b"There's a silence breathing through the car\n")?;
+    section.write_all(b"My mind is changing, forever is fading\n")?;
+    section.write_all(b"So we search for something else\n")?;
+    section.finish()?;
+    let mut section = writer.section("Outro")?;
+    section.write_all(b"There's a hush now in our hearts\n")?;
+    section.write_all(b"There's a hush now, it glows dark\n")?;
+    section.write_all(b"There's a hush now in our hearts\n")?;
+    section.write_all(b"There's a hush now, it glows dark (Do you feel the same thing?)\n")?;
+    section.write_all(b"There's a hush now in our hearts (All the lights keep blurring)\n")?;
+    section.write_all(b"There's a hush now, it glows dark (There's a silence breathing)\n")?;
+    section.write_all(b"There's a hush now in our hearts (Can you see it changing?)\n")?;
+    section.write_all(b"There's a hush now\n")?;
+    section.finish()?;
     writer.finish()?;
 
     let reader = Reader::new(&path)?;
@@ -0,0 +1,55 @@
+mod common;
+
+use common::write_cherry_pie;
+use sfa::{RandomAccessSource, Reader};
+use std::io::Read;
+
+fn read_sections(source: impl RandomAccessSource + Copy) -> Result<Vec<Vec<u8>>, sfa::Error> {
+    let reader = Reader::from_source(source)?;
+
+    let mut sections = vec![];
+    for entry in reader.toc().iter() {
+        let mut content = vec![];
+        entry.reader(source).read_to_end(&mut content)?;
+        sections.push(content);
+    }
+    Ok(sections)
+}
+
+#[test]
+pub fn source_bytes_and_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let bytes = write_cherry_pie(&path)?;
+
+    let expected = vec![
+        b"Glazed eyes and cherry pie\n".to_vec(),
+        b"Youth is running out, we finally feel it now\n".to_vec(),
+    ];
+
+    assert_eq!(expected, read_sections(bytes.as_slice())?);
+    assert_eq!(expected, read_sections(&bytes)?);
+    assert_eq!(expected, read_sections(&std::fs::File::open(&path)?)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn source_out_of_range() -> Result<(), sfa::Error> {
+    let bytes = b"Glazed eyes and cherry pie\n".to_vec();
+
+    let mut buf = [0; 6];
+    bytes.read_at(7, &mut buf)?;
+    assert_eq!(b"eyes a", &buf);
+
+    assert_eq!(
+        std::io::ErrorKind::UnexpectedEof,
+        bytes.read_at(25, &mut buf).unwrap_err().kind(),
+    );
+    assert_eq!(
+        std::io::ErrorKind::UnexpectedEof,
+        bytes.read_at(u64::MAX, &mut buf).unwrap_err().kind(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{write_cherry_pie, TRAILER_SIZE};
+use sfa::{Reader, ReaderOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+/// Reader that counts the reads issued to it
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    reads: usize,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+pub fn tail_read_single_read() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let bytes = write_cherry_pie(&path)?;
+
+    let mut reader = CountingReader {
+        inner: Cursor::new(bytes),
+        reads: 0,
+    };
+
+    let archive = Reader::from_reader(&mut reader)?;
+    assert_eq!(2, archive.toc().len());
+    assert_eq!(1, reader.reads);
+
+    Ok(())
+}
+
+#[test]
+pub fn tail_read_oversized_toc() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let bytes = write_cherry_pie(&path)?;
+
+    let mut reader = CountingReader {
+        inner: Cursor::new(bytes),
+        reads: 0,
+    };
+
+    // Only the trailer fits into the tail
+    let options = ReaderOptions::default().tail_size(TRAILER_SIZE as u64);
+
+    let archive = Reader::from_reader_with_options(&mut reader, &options)?;
+    assert_eq!(2, archive.toc().len());
+    assert_eq!(b"Verse 1", archive.toc()[0].name());
+    assert_eq!(2, reader.reads);
+
+    Ok(())
+}
+
+#[test]
+pub fn tail_read_tail_larger_than_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_cherry_pie(&path)?;
+
+    let options = ReaderOptions::default().tail_size(u64::MAX);
+
+    let archive = Reader::with_options(&path, &options)?;
+    assert_eq!(2, archive.toc().len());
+    archive.verify(&path)?;
+
+    Ok(())
+}
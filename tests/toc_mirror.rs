@@ -0,0 +1,207 @@
+mod common;
+
+use common::TRAILER_SIZE;
+use sfa::{Reader, Writer};
+use std::io::{Read, Write};
+
+fn write_archive(path: &std::path::Path, toc_mirror: bool) -> Result<(), sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?.use_toc_mirror(toc_mirror);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Returns the position of the primary ToC.
+fn toc_pos(bytes: &[u8]) -> usize {
+    let trailer = &bytes[bytes.len() - TRAILER_SIZE..];
+    u64::from_le_bytes(trailer[32..40].try_into().unwrap()) as usize
+}
+
+/// Returns the position where the mirrored trailer ends, skipping the padding before the primary ToC.
+fn mirror_end(bytes: &[u8]) -> usize {
+    let pos = toc_pos(bytes);
+    bytes[..pos].iter().rposition(|byte| *byte != 0).unwrap() + 1
+}
+
+#[test]
+pub fn toc_mirror_aligned() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, true)?;
+
+    // NOTE: The mirror and the primary ToC do not share a page
+    let bytes = std::fs::read(&path)?;
+    assert_eq!(0, toc_pos(&bytes) % 4_096);
+    assert!(mirror_end(&bytes) <= toc_pos(&bytes));
+    assert!(Reader::validate(&path)?.is_valid());
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_fallback() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, true)?;
+
+    // Corrupt primary ToC
+    let mut bytes = std::fs::read(&path)?;
+    let pos = toc_pos(&bytes);
+    bytes[pos + 10] ^= 0xFF;
+    std::fs::write(&path, &bytes)?;
+
+    let reader = Reader::new(&path)?;
+    reader.verify(&path)?;
+
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(b"Chorus", toc[1].name());
+
+    let bytes = toc[1]
+        .buf_reader(&path)?
+        .bytes()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(bytes, b"Youth is running out, we finally feel it now\n");
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_both_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, true)?;
+
+    let mut bytes = std::fs::read(&path)?;
+    let pos = toc_pos(&bytes);
+    bytes[pos + 10] ^= 0xFF;
+
+    // Corrupt last byte of mirrored ToC, which is directly followed by the mirrored trailer
+    let mirror_end = mirror_end(&bytes);
+    bytes[mirror_end - TRAILER_SIZE - 1] ^= 0xFF;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::ChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_disabled() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false)?;
+
+    let mut bytes = std::fs::read(&path)?;
+    let pos = toc_pos(&bytes);
+    bytes[pos + 10] ^= 0xFF;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::ChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_trailer_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, true)?;
+
+    // Corrupt the primary trailer, so the mirrored trailer needs to be found by searching for it
+    for pos in [TRAILER_SIZE - 10, 1] {
+        let mut bytes = std::fs::read(&path)?;
+        let len = bytes.len();
+        bytes[len - pos] ^= 0xFF;
+        std::fs::write(dir.path().join("corrupted"), &bytes)?;
+
+        let reader = Reader::new(dir.path().join("corrupted"))?;
+        reader.verify(dir.path().join("corrupted"))?;
+        assert_eq!(2, reader.toc().len());
+        assert_eq!(b"Chorus", reader.toc()[1].name());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_trailer_nested() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let nested = dir.path().join("nested");
+    let path = dir.path().join("cherry_pie");
+    write_archive(&nested, true)?;
+
+    // An archive stored in a section has a mirrored trailer as well, which must not be used
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Nested")?
+        .write_all(&std::fs::read(&nested)?)?;
+    writer.finish()?;
+
+    let mut bytes = std::fs::read(&path)?;
+    let len = bytes.len();
+    bytes[len - 1] ^= 0xFF;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(Reader::new(&path).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn toc_mirror_trailer_corrupted_without_mirror() -> Result<(), sfa::Error> {
+    /// Source that counts the bytes read from it
+    struct CountingSource {
+        bytes: Vec<u8>,
+        read: std::cell::Cell<u64>,
+    }
+
+    impl sfa::RandomAccessSource for CountingSource {
+        fn size(&self) -> std::io::Result<u64> {
+            self.bytes.as_slice().size()
+        }
+
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+            self.read.set(self.read.get() + buf.len() as u64);
+            self.bytes.as_slice().read_at(offset, buf)
+        }
+    }
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer.section("Verse 1")?.write_all(&vec![0; 4_000_000])?;
+    writer.finish()?;
+
+    let mut bytes = std::fs::read(&path)?;
+    let len = bytes.len();
+    bytes[len - 1] ^= 0xFF;
+
+    // NOTE: Only the aligned positions where a mirrored ToC could start are checked,
+    // instead of scanning the end of the file
+    let source = CountingSource {
+        bytes,
+        read: std::cell::Cell::new(0),
+    };
+    assert!(Reader::from_source(&source).is_err());
+    assert!(
+        source.read.get() < 100_000,
+        "read {} bytes",
+        source.read.get()
+    );
+
+    Ok(())
+}
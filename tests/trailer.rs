@@ -0,0 +1,86 @@
+mod common;
+
+use common::{fix_trailer_checksum, write_cherry_pie, TRAILER_SIZE};
+use sfa::Reader;
+
+/// Modifies the trailer and fixes up the trailer checksum.
+fn modify_trailer(bytes: &mut [u8], f: impl FnOnce(&mut [u8])) {
+    let trailer_pos = bytes.len() - TRAILER_SIZE;
+    let trailer = &mut bytes[trailer_pos..];
+
+    f(trailer);
+
+    fix_trailer_checksum(trailer);
+}
+
+#[test]
+pub fn trailer_checksum_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let mut bytes = write_cherry_pie(&path)?;
+
+    // Flip a bit in the ToC pos
+    let idx = bytes.len() - TRAILER_SIZE + 32;
+    bytes[idx] ^= 0b1000_0000;
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TrailerChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn trailer_toc_out_of_bounds() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let mut bytes = write_cherry_pie(&path)?;
+
+    modify_trailer(&mut bytes, |trailer| {
+        // Increment the ToC len
+        trailer[40] += 1;
+    });
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TocOutOfBounds { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn trailer_toc_length_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let bytes = write_cherry_pie(&path)?;
+
+    let trailer_pos = bytes.len() - TRAILER_SIZE;
+    let toc_pos = u64::from_le_bytes(
+        bytes[trailer_pos + 32..trailer_pos + 40]
+            .try_into()
+            .unwrap(),
+    );
+
+    // Append garbage to the ToC and fix up its checksum
+    let mut toc = bytes[toc_pos as usize..trailer_pos].to_vec();
+    toc.push(0);
+    let toc_checksum = xxhash_rust::xxh3::xxh3_128(&toc);
+
+    let mut bytes = [&bytes[..toc_pos as usize], &toc, &bytes[trailer_pos..]].concat();
+    modify_trailer(&mut bytes, |trailer| {
+        trailer[..16].copy_from_slice(&toc_checksum.to_le_bytes());
+        trailer[40..48].copy_from_slice(&(toc.len() as u64).to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TocLengthMismatch { .. })
+    ));
+
+    Ok(())
+}
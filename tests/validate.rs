@@ -0,0 +1,232 @@
+mod common;
+
+use common::{fix_trailer_checksum, TRAILER_SIZE};
+use sfa::{Reader, ValidationIssue, Writer};
+use std::io::Write;
+
+fn write_archive(path: &std::path::Path, markers: bool, mirror: bool) -> Result<(), sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?
+        .use_section_markers(markers)
+        .use_toc_mirror(mirror);
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("Verse 2")?.finish()?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Replaces the ToC and fixes up the trailer.
+fn replace_toc(bytes: &[u8], f: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let trailer_pos = bytes.len() - TRAILER_SIZE;
+    let toc_pos = u64::from_le_bytes(
+        bytes[trailer_pos + 32..trailer_pos + 40]
+            .try_into()
+            .unwrap(),
+    );
+
+    let mut toc = bytes[toc_pos as usize..trailer_pos].to_vec();
+    f(&mut toc);
+
+    let mut trailer = bytes[trailer_pos..].to_vec();
+    trailer[..16].copy_from_slice(&xxhash_rust::xxh3::xxh3_128(&toc).to_le_bytes());
+    trailer[40..48].copy_from_slice(&(toc.len() as u64).to_le_bytes());
+
+    fix_trailer_checksum(&mut trailer);
+
+    [&bytes[..toc_pos as usize], &toc, &trailer].concat()
+}
+
+#[test]
+pub fn validate_valid() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for markers in [false, true] {
+        for mirror in [false, true] {
+            let path = dir.path().join(format!("cherry_pie_{markers}_{mirror}"));
+            write_archive(&path, markers, mirror)?;
+
+            let report = Reader::validate(&path)?;
+            assert!(report.is_valid(), "{:?}", report.issues());
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_trailing_bytes() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    let mut bytes = std::fs::read(&path)?;
+    let len = bytes.len() as u64;
+    bytes.extend_from_slice(b"garbage");
+    std::fs::write(&path, &bytes)?;
+
+    assert!(Reader::new(&path).is_err());
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [ValidationIssue::TrailingBytes { pos: len, len: 7 }],
+        report.issues(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_duplicate_section_name() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer
+        .section("Verse")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [ValidationIssue::DuplicateSectionName {
+            section: b"Verse".to_vec(),
+        }],
+        report.issues(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_overlapping_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    // Move "Chorus" from 27..72 to 5..50, so it overlaps "Verse 1"
+    // and the bytes 50..72 are unreferenced
+    let bytes = replace_toc(&std::fs::read(&path)?, |toc| {
+        // magic + count + "Verse 1" entry + "Verse 2" entry
+        let idx = 4 + 4 + (8 + 8 + 2 + 7 + 16) * 2;
+        toc[idx..idx + 8].copy_from_slice(&5u64.to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [
+            ValidationIssue::OverlappingSections {
+                first: b"Verse 1".to_vec(),
+                second: b"Chorus".to_vec(),
+            },
+            ValidationIssue::UnreferencedBytes { pos: 50, len: 22 },
+        ],
+        report.issues(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_nested_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    // Move "Verse 1" from 0..27 to 30..40, so it lies within "Chorus" (without being an alias)
+    // and the bytes 0..27 are unreferenced
+    let bytes = replace_toc(&std::fs::read(&path)?, |toc| {
+        // magic + count
+        let idx = 4 + 4;
+        toc[idx..idx + 8].copy_from_slice(&30u64.to_le_bytes());
+        toc[idx + 8..idx + 16].copy_from_slice(&10u64.to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [
+            ValidationIssue::OverlappingSections {
+                first: b"Chorus".to_vec(),
+                second: b"Verse 1".to_vec(),
+            },
+            ValidationIssue::UnreferencedBytes { pos: 0, len: 27 },
+        ],
+        report.issues(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_invalid_alias() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut writer = Writer::new_at_path(&path)?;
+    writer
+        .section("Verse 1")?
+        .write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.section("Verse 2")?.finish()?;
+    writer
+        .section("Chorus")?
+        .write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.alias("latest", "Chorus")?;
+    writer.finish()?;
+    assert!(Reader::validate(&path)?.is_valid());
+
+    // Move "latest" from 27..72 to 20..65, so it spans "Verse 1" and "Chorus"
+    let bytes = replace_toc(&std::fs::read(&path)?, |toc| {
+        // magic + count + "Verse 1" entry + "Verse 2" entry + "Chorus" entry
+        let idx = 4 + 4 + (8 + 8 + 2 + 7 + 16) * 2 + (8 + 8 + 2 + 6 + 16);
+        toc[idx..idx + 8].copy_from_slice(&20u64.to_le_bytes());
+    });
+    std::fs::write(&path, &bytes)?;
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [ValidationIssue::InvalidAlias {
+            section: b"latest".to_vec(),
+        }],
+        report.issues(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn validate_toc_length_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, false, false)?;
+
+    // Append a byte to the ToC (which is 130 bytes long and starts at 72)
+    let bytes = replace_toc(&std::fs::read(&path)?, |toc| toc.push(0));
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::TocLengthMismatch { .. })
+    ));
+
+    let report = Reader::validate(&path)?;
+    assert_eq!(
+        [
+            ValidationIssue::TocLengthMismatch {
+                expected: 131,
+                got: 130,
+            },
+            ValidationIssue::UnreferencedBytes { pos: 202, len: 1 },
+        ],
+        report.issues(),
+    );
+
+    Ok(())
+}
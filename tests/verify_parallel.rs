@@ -0,0 +1,90 @@
+use sfa::{Reader, Writer};
+use std::{
+    io::Write,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+fn write_archive(path: &std::path::Path) -> Result<Vec<(u64, u64)>, sfa::Error> {
+    let mut writer = Writer::new_at_path(path)?;
+
+    for idx in 0..20u32 {
+        let mut section = writer.section(format!("Section {idx}"))?;
+        for _ in 0..idx * 10_000 {
+            section.write_all(&idx.to_le_bytes())?;
+        }
+    }
+
+    let archive = writer.finish()?;
+
+    Ok(archive
+        .toc()
+        .iter()
+        .map(|entry| (entry.pos(), entry.len()))
+        .collect())
+}
+
+#[test]
+pub fn verify_parallel() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let sections = write_archive(&path)?;
+
+    let total = sections.iter().map(|(_, len)| len).sum::<u64>();
+    let max_progress = AtomicU64::new(0);
+
+    let reader = Reader::new(&path)?;
+
+    for threads in [0, 1, 4] {
+        max_progress.store(0, Ordering::Relaxed);
+
+        reader.verify_parallel(&path, threads, |verified, t| {
+            assert_eq!(total, t);
+            max_progress.fetch_max(verified, Ordering::Relaxed);
+        })?;
+
+        assert_eq!(total, max_progress.load(Ordering::Relaxed));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn verify_parallel_reports_all() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let sections = write_archive(&path)?;
+
+    let reader = Reader::new(&path)?;
+
+    let mut bytes = std::fs::read(&path)?;
+    for idx in [3, 7, 19] {
+        let (pos, _) = sections[idx];
+        bytes[pos as usize + 5] ^= 1;
+    }
+    std::fs::write(&path, &bytes)?;
+
+    let Err(sfa::Error::CorruptedSections {
+        path: Some(error_path),
+        errors,
+    }) = reader.verify_parallel(&path, 4, |_, _| {})
+    else {
+        panic!("expected corrupted sections");
+    };
+    assert_eq!(path, error_path);
+
+    let failed = errors
+        .iter()
+        .map(|e| match e {
+            sfa::Error::ChecksumMismatch {
+                section: Some(section),
+                ..
+            } => String::from_utf8_lossy(section).into_owned(),
+            e => panic!("unexpected error: {e}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(["Section 3", "Section 7", "Section 19"], *failed);
+
+    assert!(reader.verify(&path).is_err());
+
+    Ok(())
+}